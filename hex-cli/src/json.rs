@@ -0,0 +1,204 @@
+use std::convert::TryFrom;
+
+use hex_core::{Board, Cell};
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct GameView {
+    pub first_player: String,
+    pub second_player: String,
+    pub board: Board,
+    pub turn: usize,
+    #[serde(default)]
+    pub is_finished: bool,
+    pub winner: Option<String>,
+}
+
+// `get_game` returns a game, `get_position_at` only a board.
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum View {
+    Game(GameView),
+    Board(Board),
+}
+
+// near-cli prints results as JavaScript objects: keys aren't quoted and
+// strings are in single quotes. Turns them into JSON, leaves JSON as is.
+fn relax(input: &str) -> String {
+    let mut output = String::new();
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' | '\'' => {
+                let quote = c;
+                output.push('"');
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            output.push('\\');
+                            output.extend(chars.next());
+                        }
+                        c if c == quote => {
+                            output.push('"');
+                            break;
+                        }
+                        '"' => output.push_str("\\\""),
+                        c => output.push(c),
+                    }
+                }
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut word = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                let rest = chars.clone().find(|c| !c.is_whitespace());
+                if rest == Some(':') {
+                    output.push_str(&format!("\"{}\"", word));
+                } else {
+                    output.push_str(&word);
+                }
+            }
+            c => output.push(c),
+        }
+    }
+    output
+}
+
+// A raw RPC response keeps the returned JSON as an array of bytes in
+// `result.result`.
+fn unwrap_rpc(value: Value) -> Result<Value, String> {
+    let bytes = match value.pointer("/result/result").and_then(Value::as_array) {
+        Some(bytes) => bytes,
+        None => return Ok(value),
+    };
+    let bytes = bytes
+        .iter()
+        .map(|x| x.as_u64().and_then(|x| u8::try_from(x).ok()))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or("RPC result isn't an array of bytes.")?;
+    serde_json::from_slice(&bytes).map_err(|e| e.to_string())
+}
+
+pub fn decode(input: &str) -> Result<View, String> {
+    // Skips the lines printed before the object, like the "View call" of near-cli.
+    let start = input
+        .match_indices('{')
+        .map(|(i, _)| i)
+        .find(|&i| {
+            let before = input[..i].trim_end_matches([' ', '\t']);
+            before.is_empty() || before.ends_with('\n')
+        })
+        .ok_or("No JSON object in the input.")?;
+    let value: Value = serde_json::from_str(&relax(&input[start..])).map_err(|e| e.to_string())?;
+    let value = unwrap_rpc(value)?;
+    if value.is_null() {
+        return Err("Game not found.".to_string());
+    }
+    let view =
+        serde_json::from_value(value).map_err(|e| format!("Not a game or a board: {}", e))?;
+    let board = match &view {
+        View::Game(game) => &game.board,
+        View::Board(board) => board,
+    };
+    let empty = Board::new(board.width, board.height).map_err(|e| e.to_string())?;
+    if board.field.len() != empty.field.len() {
+        return Err("Length of the field doesn't match the board size.".to_string());
+    }
+    let mut cells = (0..board.height).flat_map(|y| (0..board.width).map(move |x| Cell::new(x, y)));
+    if cells.any(|cell| board.get_cell_unchecked(&cell) > 2) {
+        return Err("Field contains an incorrect cell value.".to_string());
+    }
+    Ok(view)
+}
+
+#[cfg(test)]
+mod json_tests {
+    use hex_core::{Board, Cell};
+
+    use super::{decode, relax, View};
+
+    #[test]
+    fn test_relax() {
+        assert_eq!(
+            relax("{ width: 2, field: 'BA==', winner: null, name: 'it\"s' }"),
+            "{ \"width\": 2, \"field\": \"BA==\", \"winner\": null, \"name\": \"it\\\"s\" }"
+        );
+        let json = "{\"a\": \"b: c\", \"d\": [true, false]}";
+        assert_eq!(relax(json), json);
+    }
+
+    #[test]
+    fn test_decode_board() {
+        let mut board = Board::new(2, 2).unwrap();
+        board.set_cell(&Cell::new(0, 0), 1).unwrap();
+        assert_eq!(
+            decode("{\"width\":2,\"height\":2,\"field\":\"AQ==\"}"),
+            Ok(View::Board(board))
+        );
+        assert!(decode("{\"width\":2,\"height\":2,\"field\":\"B\"}").is_err());
+        assert_eq!(
+            decode("{\"width\":3,\"height\":3,\"field\":\"QAA=\"}"),
+            Err("Length of the field doesn't match the board size.".to_string())
+        );
+        assert_eq!(
+            decode("{\"width\":2,\"height\":2,\"field\":\"Aw==\"}"),
+            Err("Field contains an incorrect cell value.".to_string())
+        );
+        assert!(decode("[]").is_err());
+    }
+
+    #[test]
+    fn test_decode_near_cli_game() {
+        let output = "View call: hex.testnet.get_game({\"index\": 0})
+{
+  first_player: 'alice.testnet',
+  second_player: 'bob.testnet',
+  board: { width: 2, height: 2, field: 'CQ==' },
+  turn: 2,
+  current_block_height: 101,
+  prev_block_height: 100,
+  rules: { timeout: null },
+  stake: '0',
+  stake_token: null,
+  pending_acceptance: null,
+  is_finished: false,
+  winner: null,
+  finish_reason: null
+}";
+        let game = match decode(output).unwrap() {
+            View::Game(game) => game,
+            View::Board(_) => panic!("Decoded as a board"),
+        };
+        assert_eq!(game.first_player, "alice.testnet");
+        assert_eq!(game.turn, 2);
+        assert!(!game.is_finished);
+        assert_eq!(game.board.get_cell(&Cell::new(0, 0)), Ok(1));
+        assert_eq!(game.board.get_cell(&Cell::new(1, 0)), Ok(2));
+    }
+
+    #[test]
+    fn test_decode_rpc_response() {
+        let result: Vec<String> = "{\"width\":2,\"height\":2,\"field\":\"AQ==\"}"
+            .bytes()
+            .map(|x| x.to_string())
+            .collect();
+        let response = format!(
+            "{{\"jsonrpc\":\"2.0\",\"result\":{{\"block_height\":1,\"logs\":[],\"result\":[{}]}},\"id\":\"dontcare\"}}",
+            result.join(",")
+        );
+        match decode(&response).unwrap() {
+            View::Board(board) => assert_eq!(board.get_cell(&Cell::new(0, 0)), Ok(1)),
+            View::Game(_) => panic!("Decoded as a game"),
+        }
+        assert_eq!(
+            decode("{\"result\":{\"result\":[110,117,108,108]}}"),
+            Err("Game not found.".to_string())
+        );
+    }
+}
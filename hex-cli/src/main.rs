@@ -0,0 +1,469 @@
+//! Terminal client for the rules in `hex-core`: hot-seat games, replays of
+//! move lists and SGF records, boards returned by the contract and solutions
+//! of small positions.
+mod json;
+mod render;
+
+use std::env;
+use std::error::Error;
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::process;
+
+use hex_core::record::{parse_play, parse_size};
+use hex_core::solver::{solve as solve_position, SolverConfig};
+use hex_core::{parse_record, Board, GameState, Play};
+
+use crate::json::View;
+use crate::render::{player_name, render};
+
+const DEFAULT_SIZE: (usize, usize) = (11, 11);
+
+const USAGE: &str = "Usage:
+    hex-cli play [--size N|W:H] [--no-color]
+    hex-cli replay [--size N|W:H] [--all] [--no-color] [MOVES|SGF|-]
+    hex-cli show [--no-color] [JSON|-]
+    hex-cli solve [--size N|W:H] [--no-color] [MOVES|SGF|-]
+
+Without an argument or with `-` the record or the JSON is read from stdin.";
+
+type CliResult = Result<(), Box<dyn Error>>;
+
+struct Options {
+    size: Option<(usize, usize)>,
+    all: bool,
+    color: bool,
+    input: Vec<String>,
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        size: None,
+        all: false,
+        color: io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none(),
+        input: Vec::new(),
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--size" => {
+                let size = args.next().ok_or("Missing value of --size.")?;
+                options.size = Some(parse_size(size)?);
+            }
+            "--all" => options.all = true,
+            "--no-color" => options.color = false,
+            arg if arg.starts_with("--") => return Err(format!("Unknown option: {}", arg)),
+            arg => options.input.push(arg.to_string()),
+        }
+    }
+    Ok(options)
+}
+
+fn read_input(input: &[String]) -> io::Result<String> {
+    if input.is_empty() || input == ["-"] {
+        let mut buffer = String::new();
+        io::stdin().read_to_string(&mut buffer)?;
+        Ok(buffer)
+    } else {
+        Ok(input.join(" "))
+    }
+}
+
+fn print_board<W: Write>(output: &mut W, state: &GameState, color: bool) -> io::Result<()> {
+    for line in render(&state.board, color) {
+        writeln!(output, "{}", line)?;
+    }
+    Ok(())
+}
+
+fn print_status<W: Write>(output: &mut W, state: &GameState) -> io::Result<()> {
+    match (state.winner, &state.winning_path) {
+        (Some(winner), Some(path)) => {
+            let path: Vec<String> = path.iter().map(|cell| cell.to_notation()).collect();
+            writeln!(output, "{} wins: {}", player_name(winner), path.join(" "))
+        }
+        _ => writeln!(output, "{} to move.", player_name(state.current_player())),
+    }
+}
+
+fn replay_moves(start: &GameState, moves: &[Play]) -> Result<GameState, String> {
+    let mut state = start.clone();
+    for (i, play) in moves.iter().enumerate() {
+        state
+            .play(play)
+            .map_err(|e| format!("Move {} ({}): {}", i + 1, play.to_notation(), e))?;
+    }
+    Ok(state)
+}
+
+// near-cli prints the SGF of `export_sgf` in quotes after the "View call" line.
+fn strip_sgf(input: &str) -> &str {
+    match (input.find("(;"), input.rfind(')')) {
+        (Some(start), Some(end)) if start < end => &input[start..=end],
+        _ => input,
+    }
+}
+
+// Returns the position with the setup counters of the record and the moves
+// made from it.
+fn load_record(record: &str, options: &Options) -> Result<(GameState, Vec<Play>), String> {
+    let record = parse_record(strip_sgf(record))?;
+    let size = match (record.size, options.size) {
+        (Some(size), Some(option)) if size != option => {
+            return Err("Board size doesn't match the record.".to_string())
+        }
+        (size, option) => option.or(size).unwrap_or(DEFAULT_SIZE),
+    };
+    let mut board = Board::new(size.0, size.1).map_err(|e| e.to_string())?;
+    for (player, cell) in &record.setup {
+        let occupied = board.get_cell(cell).map_err(|e| e.to_string())? != 0;
+        if occupied {
+            return Err(format!(
+                "Setup counter {}: Cell is already filled.",
+                cell.to_notation()
+            ));
+        }
+        board.set_cell(cell, *player).map_err(|e| e.to_string())?;
+    }
+    let start = GameState::from_board(board, 0).map_err(|e| e.to_string())?;
+    Ok((
+        start,
+        record.moves.into_iter().map(|(_, play)| play).collect(),
+    ))
+}
+
+fn replay<W: Write>(output: &mut W, record: &str, options: &Options) -> CliResult {
+    let (start, moves) = load_record(record, options)?;
+
+    if options.all {
+        for ply in 0..moves.len() {
+            let state = replay_moves(&start, &moves[..ply])?;
+            print_board(output, &state, options.color)?;
+            writeln!(output)?;
+        }
+    }
+    let state = replay_moves(&start, &moves)?;
+    print_board(output, &state, options.color)?;
+    print_status(output, &state)?;
+    Ok(())
+}
+
+fn solve<W: Write>(output: &mut W, record: &str, options: &Options) -> CliResult {
+    let (start, moves) = load_record(record, options)?;
+    let state = replay_moves(&start, &moves)?;
+    print_board(output, &state, options.color)?;
+    if state.is_finished() {
+        return Ok(print_status(output, &state)?);
+    }
+    let solution = solve_position(&state, &SolverConfig::default())?
+        .ok_or("The position is too big to solve.")?;
+    let player = state.current_player();
+    let outcome = if solution.winner == player {
+        "wins"
+    } else {
+        "loses"
+    };
+    writeln!(
+        output,
+        "{} {} in {} plies, best move: {}",
+        player_name(player),
+        outcome,
+        solution.plies,
+        solution.best_move.as_ref().unwrap().to_notation()
+    )?;
+    Ok(())
+}
+
+fn show<W: Write>(output: &mut W, input: &str, options: &Options) -> CliResult {
+    let (board, game) = match json::decode(input)? {
+        View::Game(game) => (game.board.clone(), Some(game)),
+        View::Board(board) => (board, None),
+    };
+    for line in render(&board, options.color) {
+        writeln!(output, "{}", line)?;
+    }
+    if let Some(game) = game {
+        writeln!(
+            output,
+            "{}: {}, {}: {}",
+            player_name(1),
+            game.first_player,
+            player_name(2),
+            game.second_player
+        )?;
+        match (game.is_finished, game.winner) {
+            (true, Some(winner)) => writeln!(output, "{} won.", winner)?,
+            (true, None) => writeln!(output, "The game is cancelled.")?,
+            (false, _) => {
+                let player = (game.turn % 2) as u8 + 1;
+                writeln!(
+                    output,
+                    "Turn {}, {} to move.",
+                    game.turn,
+                    player_name(player)
+                )?
+            }
+        }
+    }
+    Ok(())
+}
+
+// Hot-seat game: both players enter their moves in turn. The list of moves is
+// printed at the end and can be replayed or used to create a game on chain.
+fn play<R: BufRead, W: Write>(input: R, output: &mut W, options: &Options) -> CliResult {
+    let size = options.size.unwrap_or(DEFAULT_SIZE);
+    let start = GameState::new(size.0, size.1)?;
+    let mut state = start.clone();
+    let mut moves: Vec<Play> = Vec::new();
+    let mut lines = input.lines();
+    print_board(output, &state, options.color)?;
+    while !state.is_finished() {
+        write!(
+            output,
+            "{} to move (cell, swap, undo or quit): ",
+            player_name(state.current_player())
+        )?;
+        output.flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+        match line.trim().to_ascii_lowercase().as_str() {
+            "" => continue,
+            "quit" => break,
+            "undo" => {
+                moves.pop();
+                state = replay_moves(&start, &moves)?;
+            }
+            value => {
+                let applied = parse_play(value)
+                    .and_then(|play| state.play(&play).map(|_| play).map_err(|e| e.to_string()));
+                match applied {
+                    Ok(play) => moves.push(play),
+                    Err(e) => {
+                        writeln!(output, "{}", e)?;
+                        continue;
+                    }
+                }
+            }
+        }
+        print_board(output, &state, options.color)?;
+    }
+    writeln!(output)?;
+    print_status(output, &state)?;
+    let moves: Vec<String> = moves.iter().map(Play::to_notation).collect();
+    writeln!(output, "Moves: {}", moves.join(" "))?;
+    Ok(())
+}
+
+fn run(args: &[String]) -> CliResult {
+    let (command, args) = args.split_first().ok_or(USAGE)?;
+    let options = parse_options(args)?;
+    let mut stdout = io::stdout();
+    match command.as_str() {
+        "play" => play(io::stdin().lock(), &mut stdout, &options),
+        "replay" => replay(&mut stdout, &read_input(&options.input)?, &options),
+        "show" => show(&mut stdout, &read_input(&options.input)?, &options),
+        "solve" => solve(&mut stdout, &read_input(&options.input)?, &options),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(USAGE.into()),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(e) = run(&args) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod cli_tests {
+    use super::{parse_options, play, replay, show, solve, strip_sgf, Options};
+
+    fn options(size: Option<(usize, usize)>, all: bool) -> Options {
+        Options {
+            size,
+            all,
+            color: false,
+            input: Vec::new(),
+        }
+    }
+
+    fn output_of<F: FnOnce(&mut Vec<u8>)>(f: F) -> Vec<String> {
+        let mut output = Vec::new();
+        f(&mut output);
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|x| x.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let args: Vec<String> = ["--size", "7:5", "f6", "--all", "swap", "--no-color"]
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+        let options = parse_options(&args).unwrap();
+        assert_eq!(options.size, Some((7, 5)));
+        assert!(options.all);
+        assert!(!options.color);
+        assert_eq!(options.input, vec!["f6", "swap"]);
+
+        assert!(parse_options(&["--size".to_string()]).is_err());
+        assert!(parse_options(&["--size".to_string(), "x".to_string()]).is_err());
+        assert!(parse_options(&["--colour".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_strip_sgf() {
+        assert_eq!(
+            strip_sgf("View call: hex.testnet.export_sgf({\"index\": 0})\n'(;FF[4]SZ[5];B[a1])'\n"),
+            "(;FF[4]SZ[5];B[a1])"
+        );
+        assert_eq!(strip_sgf(" a1 b2 "), " a1 b2 ");
+    }
+
+    #[test]
+    fn test_replay() {
+        let lines =
+            output_of(|output| replay(output, "a1 b1 a2", &options(Some((2, 2)), false)).unwrap());
+        assert_eq!(
+            lines,
+            vec!["  a b", "1 R B 1", "2  R . 2", "   a b", "Red wins: a1 a2"]
+        );
+
+        let lines = output_of(|output| {
+            replay(
+                output,
+                "(;SZ[3];B[b2];W[swap-pieces])",
+                &options(None, true),
+            )
+            .unwrap()
+        });
+        assert_eq!(lines.len(), 2 * 6 + 5 + 1);
+        assert_eq!(lines[5], "");
+        assert_eq!(lines[8], "2  . R . 2");
+        assert_eq!(lines[14], "2  . B . 2");
+        assert_eq!(lines[17], "Red to move.");
+
+        let lines = output_of(|output| {
+            replay(output, "(;SZ[2]AW[a1][b2];B[b1])", &options(None, false)).unwrap()
+        });
+        assert_eq!(
+            lines,
+            vec!["  a b", "1 B R 1", "2  . B 2", "   a b", "Blue to move."]
+        );
+    }
+
+    #[test]
+    fn test_replay_errors() {
+        let mut output = Vec::new();
+        assert_eq!(
+            replay(&mut output, "a1 a1", &options(None, false))
+                .unwrap_err()
+                .to_string(),
+            "Move 2 (a1): Cell is already filled."
+        );
+        assert_eq!(
+            replay(&mut output, "(;SZ[5];B[a1])", &options(Some((7, 7)), false))
+                .unwrap_err()
+                .to_string(),
+            "Board size doesn't match the record."
+        );
+        assert_eq!(
+            replay(&mut output, "(;SZ[5]AB[a1]AW[a1])", &options(None, false))
+                .unwrap_err()
+                .to_string(),
+            "Setup counter a1: Cell is already filled."
+        );
+        assert!(replay(&mut output, "a1 zz", &options(None, false)).is_err());
+    }
+
+    #[test]
+    fn test_solve() {
+        let lines =
+            output_of(|output| solve(output, "b1 c3 a3", &options(Some((3, 3)), false)).unwrap());
+        assert_eq!(lines[5], "Blue loses in 2 plies, best move: a2");
+
+        let lines = output_of(|output| solve(output, "(;SZ[2])", &options(None, false)).unwrap());
+        assert_eq!(lines[4], "Red loses in 4 plies, best move: a1");
+
+        let lines =
+            output_of(|output| solve(output, "a1 b1 a2", &options(Some((2, 2)), false)).unwrap());
+        assert_eq!(lines[4], "Red wins: a1 a2");
+    }
+
+    #[test]
+    fn test_show() {
+        let lines = output_of(|output| {
+            show(
+                output,
+                "{ first_player: 'alice', second_player: 'bob', board: { width: 2, height: 2, field: 'AQ==' }, turn: 1, is_finished: false, winner: null }",
+                &options(None, false),
+            )
+            .unwrap()
+        });
+        assert_eq!(
+            lines,
+            vec![
+                "  a b",
+                "1 R . 1",
+                "2  . . 2",
+                "   a b",
+                "Red: alice, Blue: bob",
+                "Turn 1, Blue to move."
+            ]
+        );
+
+        let lines = output_of(|output| {
+            show(
+                output,
+                "{\"width\":2,\"height\":2,\"field\":\"AA==\"}",
+                &options(None, false),
+            )
+            .unwrap()
+        });
+        assert_eq!(lines.len(), 4);
+    }
+
+    #[test]
+    fn test_play() {
+        let input = "b2\nswap\nzz\nc3\nundo\n\na1\nc1\nb1\na3\n";
+        let lines = output_of(|output| {
+            play(input.as_bytes(), output, &options(Some((3, 3)), false)).unwrap()
+        });
+        assert!(lines
+            .iter()
+            .any(|x| x.ends_with(": Incorrect move notation: zz")));
+        assert!(lines[lines.len() - 2].starts_with("Blue wins: "));
+        assert_eq!(lines[lines.len() - 1], "Moves: b2 swap a1 c1 b1 a3");
+
+        let lines = output_of(|output| {
+            play(
+                "a1\nb1\na2\n".as_bytes(),
+                output,
+                &options(Some((2, 2)), false),
+            )
+            .unwrap()
+        });
+        assert_eq!(lines[lines.len() - 2], "Red wins: a1 a2");
+        assert_eq!(lines[lines.len() - 1], "Moves: a1 b1 a2");
+
+        let lines = output_of(|output| {
+            play(
+                "a1\nquit\nb1\n".as_bytes(),
+                output,
+                &options(Some((2, 2)), false),
+            )
+            .unwrap()
+        });
+        assert_eq!(lines[lines.len() - 2], "Blue to move.");
+        assert_eq!(lines[lines.len() - 1], "Moves: a1");
+    }
+}
@@ -0,0 +1,93 @@
+use hex_core::{column_letters, Board};
+
+const RED: &str = "\x1b[31m";
+const BLUE: &str = "\x1b[34m";
+const RESET: &str = "\x1b[0m";
+
+fn paint(text: &str, color: &str, enabled: bool) -> String {
+    if enabled {
+        format!("{}{}{}", color, text, RESET)
+    } else {
+        text.to_string()
+    }
+}
+
+pub fn player_name(player: u8) -> &'static str {
+    if player == 1 {
+        "Red"
+    } else {
+        "Blue"
+    }
+}
+
+// Same layout as `Board::get_board_with_notation` with the letters repeated
+// under the board and the numbers on both sides. Letters are red and numbers
+// are blue, like the borders each player has to connect.
+pub fn render(board: &Board, color: bool) -> Vec<String> {
+    let label_width = board.height.to_string().len();
+    let letters: Vec<String> = (0..board.width).map(column_letters).collect();
+    let letters = paint(&letters.join(" "), RED, color);
+
+    let mut lines = vec![format!("{} {}", " ".repeat(label_width), letters)];
+    for (i, row) in board.get_board_as_strings().into_iter().enumerate() {
+        let row: String = row
+            .chars()
+            .map(|c| match c {
+                'R' => paint("R", RED, color),
+                'B' => paint("B", BLUE, color),
+                c => c.to_string(),
+            })
+            .collect();
+        let number = (i + 1).to_string();
+        lines.push(format!(
+            "{} {} {}",
+            paint(&format!("{:>1$}", number, label_width), BLUE, color),
+            row,
+            paint(&number, BLUE, color)
+        ));
+    }
+    lines.push(format!(
+        "{}{}",
+        " ".repeat(label_width + board.height),
+        letters
+    ));
+    lines
+}
+
+#[cfg(test)]
+mod render_tests {
+    use hex_core::{Board, Cell};
+
+    use super::render;
+
+    #[test]
+    fn test_render() {
+        let mut board = Board::new(3, 10).unwrap();
+        board.set_cell(&Cell::new(1, 0), 1).unwrap();
+        board.set_cell(&Cell::new(2, 9), 2).unwrap();
+        let lines = render(&board, false);
+        assert_eq!(lines.len(), 12);
+        assert_eq!(lines[0], "   a b c");
+        assert_eq!(lines[1], " 1 . R . 1");
+        assert_eq!(lines[2], " 2  . . . 2");
+        assert_eq!(lines[10], "10          . . B 10");
+        assert_eq!(lines[11], "            a b c");
+    }
+
+    #[test]
+    fn test_render_color() {
+        let mut board = Board::new(2, 2).unwrap();
+        board.set_cell(&Cell::new(0, 0), 1).unwrap();
+        board.set_cell(&Cell::new(1, 1), 2).unwrap();
+        let lines = render(&board, true);
+        assert_eq!(lines[0], "  \x1b[31ma b\x1b[0m");
+        assert_eq!(
+            lines[1],
+            "\x1b[34m1\x1b[0m \x1b[31mR\x1b[0m . \x1b[34m1\x1b[0m"
+        );
+        assert_eq!(
+            lines[2],
+            "\x1b[34m2\x1b[0m  . \x1b[34mB\x1b[0m \x1b[34m2\x1b[0m"
+        );
+    }
+}
@@ -0,0 +1,245 @@
+use std::time::{Duration, Instant};
+
+use crate::error::Error;
+use crate::record::Play;
+use crate::state::GameState;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MctsConfig {
+    pub playouts: u32,
+    // The search stops at whichever limit comes first. Results with a time
+    // budget depend on the speed of the machine, not only on the seed.
+    pub time_budget: Option<Duration>,
+    pub seed: u64,
+    pub exploration: f64,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self {
+            playouts: 10_000,
+            time_budget: None,
+            seed: 0,
+            exploration: std::f64::consts::SQRT_2,
+        }
+    }
+}
+
+// SplitMix64, good enough for playouts and the same on every platform.
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+struct Node {
+    play: Option<Play>,
+    // The player who made the move leading to the node.
+    player: u8,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<Play>,
+    visits: u32,
+    wins: u32,
+}
+
+// Hex can't end in a draw, so the random playout always has a winner.
+fn playout(state: &mut GameState, rng: &mut Rng) -> Result<u8, Error> {
+    let mut cells: Vec<Play> = state
+        .legal_moves()
+        .into_iter()
+        .filter(|play| *play != Play::Swap)
+        .collect();
+    while state.winner.is_none() {
+        let play = cells.swap_remove(rng.below(cells.len()));
+        state.play(&play)?;
+    }
+    Ok(state.winner.unwrap())
+}
+
+fn new_node(state: &GameState, play: Option<Play>, parent: Option<usize>) -> Node {
+    Node {
+        play,
+        player: 3 - state.current_player(),
+        parent,
+        children: Vec::new(),
+        untried: state.legal_moves(),
+        visits: 0,
+        wins: 0,
+    }
+}
+
+fn uct(node: &Node, parent_visits: u32, exploration: f64) -> f64 {
+    let visits = node.visits as f64;
+    node.wins as f64 / visits + exploration * ((parent_visits as f64).ln() / visits).sqrt()
+}
+
+/// Monte Carlo Tree Search over the rules of `GameState`: returns the move
+/// with the most visits for the player to move.
+pub fn best_move(state: &GameState, config: &MctsConfig) -> Result<Play, Error> {
+    if state.is_finished() {
+        return Err(Error::GameFinished);
+    }
+    // `Instant` isn't available everywhere, e.g. in the browser, so it's only
+    // used with a time budget.
+    let deadline = config.time_budget.map(|budget| Instant::now() + budget);
+    let mut rng = Rng(config.seed);
+    let mut nodes = vec![new_node(state, None, None)];
+
+    for _ in 0..config.playouts {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            break;
+        }
+
+        let mut current = state.clone();
+        let mut index = 0;
+        // Selection.
+        while nodes[index].untried.is_empty() && !nodes[index].children.is_empty() {
+            let parent_visits = nodes[index].visits;
+            index = *nodes[index]
+                .children
+                .iter()
+                .max_by(|&&a, &&b| {
+                    uct(&nodes[a], parent_visits, config.exploration).total_cmp(&uct(
+                        &nodes[b],
+                        parent_visits,
+                        config.exploration,
+                    ))
+                })
+                .unwrap();
+            current.play(nodes[index].play.as_ref().unwrap())?;
+        }
+        // Expansion.
+        if !nodes[index].untried.is_empty() {
+            let untried = &mut nodes[index].untried;
+            let play = untried.swap_remove(rng.below(untried.len()));
+            current.play(&play)?;
+            let child = nodes.len();
+            nodes.push(new_node(&current, Some(play), Some(index)));
+            nodes[index].children.push(child);
+            index = child;
+        }
+        // Simulation and backpropagation.
+        let winner = playout(&mut current, &mut rng)?;
+        let mut node = Some(index);
+        while let Some(i) = node {
+            nodes[i].visits += 1;
+            if nodes[i].player == winner {
+                nodes[i].wins += 1;
+            }
+            node = nodes[i].parent;
+        }
+    }
+
+    let best = nodes[0]
+        .children
+        .iter()
+        .max_by_key(|&&child| nodes[child].visits)
+        .map(|&child| nodes[child].play.clone().unwrap());
+    // Without a single playout the first legal move is returned.
+    best.or_else(|| state.legal_moves().into_iter().next())
+        .ok_or(Error::GameFinished)
+}
+
+#[cfg(test)]
+mod ai_tests {
+    use std::time::{Duration, Instant};
+
+    use super::{best_move, MctsConfig};
+    use crate::{board::Board, cell::Cell, error::Error, record::Play, state::GameState};
+
+    fn config(playouts: u32) -> MctsConfig {
+        MctsConfig {
+            playouts,
+            ..MctsConfig::default()
+        }
+    }
+
+    fn position(
+        width: usize,
+        height: usize,
+        red: &[(usize, usize)],
+        blue: &[(usize, usize)],
+    ) -> GameState {
+        let mut board = Board::new(width, height).unwrap();
+        for &(x, y) in red {
+            board.set_cell(&Cell::new(x, y), 1).unwrap();
+        }
+        for &(x, y) in blue {
+            board.set_cell(&Cell::new(x, y), 2).unwrap();
+        }
+        GameState::from_board(board, red.len() + blue.len()).unwrap()
+    }
+
+    #[test]
+    fn test_winning_move() {
+        let state = position(3, 3, &[(0, 0), (0, 1)], &[(2, 0), (2, 2)]);
+        assert_eq!(
+            best_move(&state, &config(2000)),
+            Ok(Play::Place(Cell::new(0, 2)))
+        );
+    }
+
+    #[test]
+    fn test_blocking_move() {
+        let state = position(4, 4, &[(0, 0), (1, 0), (3, 0)], &[(1, 3), (2, 3), (3, 3)]);
+        assert_eq!(
+            best_move(&state, &config(3000)),
+            Ok(Play::Place(Cell::new(0, 3)))
+        );
+    }
+
+    #[test]
+    fn test_swap_decision() {
+        let mut state = GameState::new(3, 3).unwrap();
+        state.place(&Cell::new(1, 1), 1).unwrap();
+        assert_eq!(best_move(&state, &config(3000)), Ok(Play::Swap));
+    }
+
+    #[test]
+    fn test_seed_is_deterministic() {
+        let state = GameState::new(5, 5).unwrap();
+        for seed in 0..3 {
+            let config = MctsConfig {
+                playouts: 200,
+                seed,
+                ..MctsConfig::default()
+            };
+            assert_eq!(best_move(&state, &config), best_move(&state, &config));
+        }
+    }
+
+    #[test]
+    fn test_time_budget() {
+        let state = GameState::new(11, 11).unwrap();
+        let config = MctsConfig {
+            playouts: u32::MAX,
+            time_budget: Some(Duration::from_millis(50)),
+            ..MctsConfig::default()
+        };
+        let start = Instant::now();
+        assert!(matches!(best_move(&state, &config), Ok(Play::Place(_))));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_best_move_edge_cases() {
+        let state = GameState::new(2, 2).unwrap();
+        assert_eq!(
+            best_move(&state, &config(0)),
+            Ok(Play::Place(Cell::new(0, 0)))
+        );
+        let state = position(2, 2, &[(0, 0), (0, 1)], &[(1, 0)]);
+        assert_eq!(best_move(&state, &config(10)), Err(Error::GameFinished));
+    }
+}
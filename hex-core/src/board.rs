@@ -0,0 +1,311 @@
+use alloc::{format, string::String, string::ToString, vec, vec::Vec};
+
+use crate::cell::{column_letters, Cell};
+use crate::error::Error;
+
+pub const MAX_FIELD_SIZE: usize = 32;
+
+// Every cell takes two bits of the field: 0 is empty, 1 and 2 are the players.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Board {
+    pub width: usize,
+    pub height: usize,
+    #[cfg_attr(feature = "serde", serde(with = "base64_field"))]
+    pub field: Vec<u8>,
+}
+
+impl Board {
+    pub fn new(width: usize, height: usize) -> Result<Self, Error> {
+        if width > MAX_FIELD_SIZE || height > MAX_FIELD_SIZE {
+            return Err(Error::FieldTooBig);
+        }
+        if width == 0 || height == 0 {
+            return Err(Error::EmptyField);
+        }
+        let field_len = (width * height).div_ceil(4);
+        Ok(Board {
+            width,
+            height,
+            field: vec![0u8; field_len],
+        })
+    }
+
+    pub fn contains(&self, cell: &Cell) -> bool {
+        cell.x < self.width && cell.y < self.height
+    }
+
+    fn get_byte_and_bit(&self, cell: &Cell) -> (u8, usize, usize) {
+        let index = (self.width * cell.y + cell.x) * 2;
+        let byte_index = index / 8;
+        let byte: u8 = self.field[byte_index];
+        let bit_index = index & 7;
+        (byte, byte_index, bit_index)
+    }
+
+    pub fn get_cell(&self, cell: &Cell) -> Result<u8, Error> {
+        if !self.contains(cell) {
+            return Err(Error::CellOutOfBounds);
+        }
+        Ok(self.get_cell_unchecked(cell))
+    }
+
+    // For the cells that are known to be on the board, e.g. neighbours of a cell.
+    pub fn get_cell_unchecked(&self, cell: &Cell) -> u8 {
+        let (byte, _, bit_index) = self.get_byte_and_bit(cell);
+        (byte >> bit_index) & 3
+    }
+
+    pub fn set_cell(&mut self, cell: &Cell, value: u8) -> Result<(), Error> {
+        if !self.contains(cell) {
+            return Err(Error::CellOutOfBounds);
+        }
+        if value > 2 {
+            return Err(Error::IncorrectCellValue);
+        }
+        let (byte, byte_index, bit_index) = self.get_byte_and_bit(cell);
+        let bits = (byte >> bit_index) & 3;
+        let new_byte = byte ^ (bits << bit_index) ^ (value << bit_index);
+        self.field[byte_index] = new_byte;
+        Ok(())
+    }
+
+    pub fn get_coords(&self, bit_number: usize) -> Cell {
+        Cell::new(bit_number / 2 % self.width, bit_number / 2 / self.width)
+    }
+
+    pub fn get_board_as_strings(&self) -> Vec<String> {
+        let mut vector = Vec::new();
+
+        for i in 0..self.height {
+            let mut result: String = (0..i).map(|_| ' ').collect();
+            for j in 0..self.width {
+                let symbol = match self.get_cell_unchecked(&Cell::new(j, i)) {
+                    0 => '.',
+                    1 => 'R',
+                    2 => 'B',
+                    _ => unreachable!(),
+                };
+                result.push(symbol);
+                if j + 1 != self.width {
+                    result.push(' ');
+                }
+            }
+            vector.push(result);
+        }
+
+        vector
+    }
+
+    pub fn get_board_with_notation(&self) -> Vec<String> {
+        let label_width = self.height.to_string().len();
+        let letters: Vec<String> = (0..self.width).map(column_letters).collect();
+        let mut vector = vec![format!("{} {}", " ".repeat(label_width), letters.join(" "))];
+        for (i, row) in self.get_board_as_strings().into_iter().enumerate() {
+            vector.push(format!("{:>width$} {}", i + 1, row, width = label_width));
+        }
+        vector
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.width == self.height
+    }
+}
+
+// The field is a base64 string in JSON, like `Base64VecU8` of near-sdk.
+#[cfg(feature = "serde")]
+mod base64_field {
+    use alloc::{string::String, vec::Vec};
+
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(field: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base64::encode(field))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        base64::decode(encoded).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod board_tests {
+    use alloc::vec;
+
+    use crate::{cell::Cell, error::Error};
+
+    use super::Board;
+
+    #[test]
+    fn test_board_is_too_big() {
+        assert_eq!(Board::new(33, 11), Err(Error::FieldTooBig));
+    }
+
+    #[test]
+    fn test_board_is_too_high() {
+        assert_eq!(Board::new(11, 33), Err(Error::FieldTooBig));
+    }
+
+    #[test]
+    fn test_empty_board() {
+        assert_eq!(Board::new(0, 0), Err(Error::EmptyField));
+        assert_eq!(Board::new(0, 11), Err(Error::EmptyField));
+        assert_eq!(Board::new(11, 0), Err(Error::EmptyField));
+    }
+
+    #[test]
+    fn test_rectangular_board() {
+        let mut test_board = Board::new(3, 5).unwrap();
+        assert_eq!(test_board.field.len(), 4);
+        assert!(!test_board.is_square());
+        assert!(Board::new(32, 32).unwrap().is_square());
+
+        test_board.set_cell(&Cell::new(2, 4), 1).unwrap();
+        test_board.set_cell(&Cell::new(0, 1), 2).unwrap();
+        assert_eq!(Ok(1), test_board.get_cell(&Cell::new(2, 4)));
+        assert_eq!(Ok(2), test_board.get_cell(&Cell::new(0, 1)));
+        assert_eq!((128, 0, 6), test_board.get_byte_and_bit(&Cell::new(0, 1)));
+        assert_eq!(Cell::new(2, 4), test_board.get_coords(28));
+        assert_eq!(
+            test_board.get_board_as_strings(),
+            vec![". . .", " B . .", "  . . .", "   . . .", "    . . R"]
+        );
+    }
+
+    #[test]
+    fn test_get_board_with_notation() {
+        let mut test_board = Board::new(3, 10).unwrap();
+        test_board.set_cell(&Cell::new(1, 0), 1).unwrap();
+        test_board.set_cell(&Cell::new(2, 9), 2).unwrap();
+        let strings = test_board.get_board_with_notation();
+        assert_eq!(strings.len(), 11);
+        assert_eq!(strings[0], "   a b c");
+        assert_eq!(strings[1], " 1 . R .");
+        assert_eq!(strings[2], " 2  . . .");
+        assert_eq!(strings[10], "10          . . B");
+    }
+
+    #[test]
+    fn test_rectangular_board_out_of_bounds() {
+        assert_eq!(
+            Board::new(3, 5).unwrap().get_cell(&Cell::new(3, 0)),
+            Err(Error::CellOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_get_byte_and_bit() {
+        let mut test_board = Board::new(11, 11).unwrap();
+        assert_eq!((0, 3, 6), test_board.get_byte_and_bit(&Cell::new(4, 1)));
+        assert_eq!((0, 0, 0), test_board.get_byte_and_bit(&Cell::new(0, 0)));
+        assert_eq!((0, 5, 0), test_board.get_byte_and_bit(&Cell::new(9, 1)));
+
+        let mut test_vec = vec![0u8; 7];
+        test_vec[0] = 255;
+        test_vec[1] = 19;
+        test_vec[2] = 7;
+        test_vec[3] = 113;
+        test_board = Board {
+            width: 5,
+            height: 5,
+            field: test_vec,
+        };
+
+        assert_eq!((255, 0, 6), test_board.get_byte_and_bit(&Cell::new(3, 0)));
+        assert_eq!((7, 2, 6), test_board.get_byte_and_bit(&Cell::new(1, 2)));
+        assert_eq!((19, 1, 0), test_board.get_byte_and_bit(&Cell::new(4, 0)));
+        assert_eq!((0, 6, 0), test_board.get_byte_and_bit(&Cell::new(4, 4)));
+    }
+
+    #[test]
+    fn test_get_cell_out_of_bounds() {
+        assert_eq!(
+            Board::new(11, 11).unwrap().get_cell(&Cell::new(5, 11)),
+            Err(Error::CellOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_get_cell() {
+        let mut test_vec = vec![0u8; 7];
+        test_vec[0] = 255;
+        test_vec[1] = 32;
+        test_vec[2] = 7;
+        test_vec[3] = 113;
+        let test_board = Board {
+            width: 5,
+            height: 5,
+            field: test_vec,
+        };
+
+        assert_eq!(Ok(3), test_board.get_cell(&Cell::new(3, 0)));
+        assert_eq!(Ok(1), test_board.get_cell(&Cell::new(4, 1)));
+        assert_eq!(Ok(3), test_board.get_cell(&Cell::new(4, 2)));
+        assert_eq!(Ok(2), test_board.get_cell(&Cell::new(1, 1)));
+    }
+
+    #[test]
+    fn test_set_cell_out_of_bounds() {
+        assert_eq!(
+            Board::new(5, 5).unwrap().set_cell(&Cell::new(6, 3), 1),
+            Err(Error::CellOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_set_cell_too_big_value() {
+        assert_eq!(
+            Board::new(4, 4).unwrap().set_cell(&Cell::new(1, 1), 3),
+            Err(Error::IncorrectCellValue)
+        );
+    }
+
+    #[test]
+    fn test_set_sell() {
+        let mut test_board = Board::new(11, 11).unwrap();
+        let test_cell = Cell::new(2, 5);
+
+        test_board.set_cell(&test_cell, 1).unwrap();
+        assert_eq!(Ok(1), test_board.get_cell(&test_cell));
+
+        test_board.set_cell(&test_cell, 0).unwrap();
+        assert_eq!(Ok(0), test_board.get_cell(&test_cell));
+
+        test_board.set_cell(&test_cell, 2).unwrap();
+        assert_eq!(Ok(2), test_board.get_cell(&test_cell));
+
+        test_board.set_cell(&Cell::new(10, 10), 1).unwrap();
+        test_board.set_cell(&Cell::new(3, 5), 1).unwrap();
+
+        assert_eq!(Ok(2), test_board.get_cell(&Cell::new(2, 5)));
+        assert_eq!(Ok(1), test_board.get_cell(&Cell::new(10, 10)));
+        assert_eq!(Ok(1), test_board.get_cell(&Cell::new(3, 5)));
+    }
+
+    #[test]
+    fn test_get_coords() {
+        let mut test_board = Board::new(5, 5).unwrap();
+        assert_eq!(Cell::new(0, 1), test_board.get_coords(10));
+        assert_eq!(Cell::new(0, 0), test_board.get_coords(0));
+        assert_eq!(Cell::new(0, 0), test_board.get_coords(1));
+        assert_eq!(Cell::new(4, 4), test_board.get_coords(48));
+
+        test_board = Board::new(15, 15).unwrap();
+        let mut test_cell = Cell::new(11, 7);
+        let (_, mut byte, mut bit) = test_board.get_byte_and_bit(&test_cell);
+        assert_eq!(test_cell, test_board.get_coords(byte * 8 + bit));
+
+        test_cell = Cell::new(0, 14);
+        (_, byte, bit) = test_board.get_byte_and_bit(&test_cell);
+        assert_eq!(test_cell, test_board.get_coords(byte * 8 + bit));
+
+        test_cell = Cell::new(10, 2);
+        (_, byte, bit) = test_board.get_byte_and_bit(&test_cell);
+        assert_eq!(test_cell, test_board.get_coords(byte * 8 + bit));
+    }
+}
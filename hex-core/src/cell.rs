@@ -0,0 +1,271 @@
+use alloc::{format, string::String, vec::Vec};
+
+use crate::error::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cell {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Cell {
+    pub fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
+
+    pub fn get_neighbours(&self, width: usize, height: usize) -> Result<Vec<Cell>, Error> {
+        if self.x >= width || self.y >= height {
+            return Err(Error::CellOutOfBounds);
+        }
+        let mut neighbours: Vec<Cell> = Vec::new();
+        let (x, y) = (self.x, self.y);
+        if self.x > 0 {
+            neighbours.push(Cell { x: x - 1, y });
+        }
+        if self.y > 0 {
+            neighbours.push(Cell { x, y: y - 1 });
+        }
+        if self.x + 1 < width && self.y > 0 {
+            neighbours.push(Cell { x: x + 1, y: y - 1 });
+        }
+        if self.x + 1 < width {
+            neighbours.push(Cell { x: x + 1, y });
+        }
+        if self.y + 1 < height {
+            neighbours.push(Cell { x, y: y + 1 });
+        }
+        if self.x > 0 && self.y + 1 < height {
+            neighbours.push(Cell { x: x - 1, y: y + 1 });
+        }
+        Ok(neighbours)
+    }
+
+    pub fn from_notation(notation: &str) -> Option<Self> {
+        let notation = notation.trim().to_ascii_lowercase();
+        let digits_start = notation.find(|c: char| c.is_ascii_digit())?;
+        let (letters, digits) = notation.split_at(digits_start);
+        if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_lowercase()) {
+            return None;
+        }
+        if !digits.chars().all(|c| c.is_ascii_digit()) || digits.starts_with('0') {
+            return None;
+        }
+        let mut column: usize = 0;
+        for c in letters.chars() {
+            column = column
+                .checked_mul(26)?
+                .checked_add((c as u8 - b'a') as usize + 1)?;
+        }
+        let row: usize = digits.parse().ok()?;
+        Some(Self::new(column - 1, row - 1))
+    }
+
+    pub fn to_notation(&self) -> String {
+        format!("{}{}", column_letters(self.x), self.y + 1)
+    }
+
+    pub fn symm(&self) -> Self {
+        Self {
+            x: self.y,
+            y: self.x,
+        }
+    }
+}
+
+// Columns are named like spreadsheet columns: a..z, aa..az, ba..
+pub fn column_letters(x: usize) -> String {
+    let mut letters = Vec::new();
+    let mut column = x + 1;
+    while column > 0 {
+        letters.push(b'a' + ((column - 1) % 26) as u8);
+        column = (column - 1) / 26;
+    }
+    letters.reverse();
+    String::from_utf8(letters).unwrap()
+}
+
+#[cfg(test)]
+mod cell_tests {
+    use alloc::vec;
+
+    use super::{column_letters, Cell};
+    use crate::error::Error;
+
+    #[test]
+    fn test_cell_neighbours_single() {
+        let test_cell = Cell::new(0, 0);
+        let neighbours = test_cell.get_neighbours(1, 1).unwrap();
+        assert!(neighbours.is_empty());
+    }
+
+    #[test]
+    fn test_cell_neighbours_center() {
+        let test_cell = Cell::new(1, 1);
+        let neighbours = test_cell.get_neighbours(3, 3).unwrap();
+        assert_eq!(
+            neighbours,
+            vec![
+                Cell::new(0, 1),
+                Cell::new(1, 0),
+                Cell::new(2, 0),
+                Cell::new(2, 1),
+                Cell::new(1, 2),
+                Cell::new(0, 2)
+            ]
+        );
+    }
+    #[test]
+    fn test_cell_neighbours_right_bottom_corner() {
+        let test_cell = Cell::new(1, 1);
+        let neighbours = test_cell.get_neighbours(2, 2).unwrap();
+        assert_eq!(neighbours, vec![Cell::new(0, 1), Cell::new(1, 0)]);
+    }
+    #[test]
+    fn test_cell_neighbours_left_upper_corner() {
+        let test_cell = Cell::new(0, 0);
+        let neighbours = test_cell.get_neighbours(4, 4).unwrap();
+        assert_eq!(neighbours, vec![Cell::new(1, 0), Cell::new(0, 1)]);
+    }
+    #[test]
+    fn test_cell_neighbours_left_bottom_corner() {
+        let test_cell = Cell::new(0, 2);
+        let neighbours = test_cell.get_neighbours(3, 3).unwrap();
+        assert_eq!(
+            neighbours,
+            vec![Cell::new(0, 1), Cell::new(1, 1), Cell::new(1, 2)]
+        );
+    }
+    #[test]
+    fn test_cell_neighbours_right_upper_corner() {
+        let test_cell = Cell::new(4, 0);
+        let neighbours = test_cell.get_neighbours(5, 5).unwrap();
+        assert_eq!(
+            neighbours,
+            vec![Cell::new(3, 0), Cell::new(4, 1), Cell::new(3, 1)]
+        );
+    }
+
+    #[test]
+    fn test_cell_neighbours_left_border() {
+        let test_cell = Cell::new(0, 1);
+        let neighbours = test_cell.get_neighbours(3, 3).unwrap();
+        assert_eq!(
+            neighbours,
+            vec![
+                Cell::new(0, 0),
+                Cell::new(1, 0),
+                Cell::new(1, 1),
+                Cell::new(0, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cell_neighbours_upper_border() {
+        let test_cell = Cell::new(2, 0);
+        let neighbours = test_cell.get_neighbours(4, 4).unwrap();
+        assert_eq!(
+            neighbours,
+            vec![
+                Cell::new(1, 0),
+                Cell::new(3, 0),
+                Cell::new(2, 1),
+                Cell::new(1, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cell_neighbours_right_border() {
+        let test_cell = Cell::new(4, 3);
+        let neighbours = test_cell.get_neighbours(5, 5).unwrap();
+        assert_eq!(
+            neighbours,
+            vec![
+                Cell::new(3, 3),
+                Cell::new(4, 2),
+                Cell::new(4, 4),
+                Cell::new(3, 4)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cell_neighbours_bottom_border() {
+        let test_cell = Cell::new(2, 5);
+        let neighbours = test_cell.get_neighbours(6, 6).unwrap();
+        assert_eq!(
+            neighbours,
+            vec![
+                Cell::new(1, 5),
+                Cell::new(2, 4),
+                Cell::new(3, 4),
+                Cell::new(3, 5)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cell_neighbours_rectangular() {
+        let test_cell = Cell::new(1, 3);
+        let neighbours = test_cell.get_neighbours(2, 4).unwrap();
+        assert_eq!(neighbours, vec![Cell::new(0, 3), Cell::new(1, 2)]);
+        let test_cell = Cell::new(3, 0);
+        let neighbours = test_cell.get_neighbours(4, 2).unwrap();
+        assert_eq!(
+            neighbours,
+            vec![Cell::new(2, 0), Cell::new(3, 1), Cell::new(2, 1)]
+        );
+    }
+
+    #[test]
+    fn test_cell_out_of_rectangular_bounds() {
+        assert_eq!(
+            Cell::new(3, 1).get_neighbours(3, 5),
+            Err(Error::CellOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_cell_of_bounds() {
+        assert_eq!(
+            Cell::new(3, 4).get_neighbours(4, 4),
+            Err(Error::CellOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_to_notation() {
+        assert_eq!(Cell::new(0, 0).to_notation(), "a1");
+        assert_eq!(Cell::new(5, 5).to_notation(), "f6");
+        assert_eq!(Cell::new(18, 18).to_notation(), "s19");
+        assert_eq!(Cell::new(26, 31).to_notation(), "aa32");
+        assert_eq!(column_letters(25), "z");
+        assert_eq!(column_letters(31), "af");
+        assert_eq!(column_letters(52), "ba");
+    }
+
+    #[test]
+    fn test_from_notation() {
+        assert_eq!(Cell::from_notation("a1"), Some(Cell::new(0, 0)));
+        assert_eq!(Cell::from_notation("F6"), Some(Cell::new(5, 5)));
+        assert_eq!(Cell::from_notation(" s19 "), Some(Cell::new(18, 18)));
+        assert_eq!(Cell::from_notation("af32"), Some(Cell::new(31, 31)));
+        for x in 0..60 {
+            let cell = Cell::new(x, x / 2);
+            assert_eq!(Cell::from_notation(&cell.to_notation()), Some(cell));
+        }
+    }
+
+    #[test]
+    fn test_from_incorrect_notation() {
+        for notation in ["", "a", "12", "a0", "a01", "1a", "a1b", "\u{e9}1", "a-1"].iter() {
+            assert_eq!(Cell::from_notation(notation), None);
+        }
+    }
+}
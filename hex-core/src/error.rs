@@ -0,0 +1,43 @@
+use core::fmt;
+
+use crate::board::MAX_FIELD_SIZE;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Error {
+    FieldTooBig,
+    EmptyField,
+    CellOutOfBounds,
+    CellOccupied,
+    IncorrectCellValue,
+    GameFinished,
+    NotYourTurn,
+    SwapNotAllowed,
+    SwapOnRectangularBoard,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::FieldTooBig => write!(
+                f,
+                "The size of the field must be less or equal {}",
+                MAX_FIELD_SIZE
+            ),
+            Error::EmptyField => f.write_str("The field must have at least one row and column."),
+            Error::CellOutOfBounds => f.write_str("Cell is out of bounds."),
+            Error::CellOccupied => f.write_str("Cell is already filled."),
+            Error::IncorrectCellValue => f.write_str("Value is too big."),
+            Error::GameFinished => f.write_str("Game is already finished!"),
+            Error::NotYourTurn => f.write_str("It's another player turn now."),
+            Error::SwapNotAllowed => {
+                f.write_str("Swap rule can be applied only on the second player first turn")
+            }
+            Error::SwapOnRectangularBoard => {
+                f.write_str("Swap rule can be applied only on a square board")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
@@ -0,0 +1,282 @@
+use alloc::{collections::VecDeque, vec, vec::Vec};
+
+use crate::board::Board;
+use crate::cell::Cell;
+
+// A player's own counters conduct almost freely. The resistance isn't zero so
+// that every conductance in the circuit stays finite.
+const OWN_CELL_RESISTANCE: f64 = 0.001;
+const EMPTY_CELL_RESISTANCE: f64 = 1.0;
+const EVEN_MARGIN: f64 = 1e-9;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerEvaluation {
+    // `None` when the opponent has cut the player's borders apart.
+    pub resistance: Option<f64>,
+    // Current through every cell, by rows, with a unit voltage between the
+    // borders. The cells carrying the most current are the critical ones.
+    pub heatmap: Vec<Vec<f64>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Evaluation {
+    pub first_player: PlayerEvaluation,
+    pub second_player: PlayerEvaluation,
+    // Chances of the first player from 0 to 1: the share of the second
+    // player's resistance in the sum of both.
+    pub first_player_estimate: f64,
+}
+
+impl Evaluation {
+    pub fn leader(&self) -> Option<u8> {
+        if self.first_player_estimate > 0.5 + EVEN_MARGIN {
+            Some(1)
+        } else if self.first_player_estimate < 0.5 - EVEN_MARGIN {
+            Some(2)
+        } else {
+            None
+        }
+    }
+}
+
+fn abs(x: f64) -> f64 {
+    if x < 0.0 {
+        -x
+    } else {
+        x
+    }
+}
+
+// Whether the cell touches the first and the last border of the player.
+fn borders(cell: &Cell, player: u8, width: usize, height: usize) -> (bool, bool) {
+    let (coordinate, length) = if player == 1 {
+        (cell.y, height)
+    } else {
+        (cell.x, width)
+    };
+    (coordinate == 0, coordinate + 1 == length)
+}
+
+// Gaussian elimination of an augmented `n x (n + 1)` matrix. The matrix of
+// Kirchhoff's equations is symmetric positive definite, so no pivoting is
+// needed.
+fn solve(mut matrix: Vec<f64>, n: usize) -> Vec<f64> {
+    let stride = n + 1;
+    for col in 0..n {
+        for row in col + 1..n {
+            let factor = matrix[row * stride + col] / matrix[col * stride + col];
+            if factor == 0.0 {
+                continue;
+            }
+            for k in col..stride {
+                matrix[row * stride + k] -= factor * matrix[col * stride + k];
+            }
+        }
+    }
+    let mut solution = vec![0.0; n];
+    for row in (0..n).rev() {
+        let mut value = matrix[row * stride + n];
+        for k in row + 1..n {
+            value -= matrix[row * stride + k] * solution[k];
+        }
+        solution[row] = value / matrix[row * stride + row];
+    }
+    solution
+}
+
+// Every empty cell is a unit resistor, cells with the opponent's counters are
+// removed, and the player's two borders are the poles of the circuit, like in
+// Hexy and Anshelevich's evaluation function.
+fn evaluate_player(board: &Board, player: u8) -> PlayerEvaluation {
+    let (width, height) = (board.width, board.height);
+    let mut heatmap = vec![vec![0.0; width]; height];
+    let is_open = |cell: &Cell| board.get_cell_unchecked(cell) != 3 - player;
+    let resistance = |cell: &Cell| {
+        if board.get_cell_unchecked(cell) == player {
+            OWN_CELL_RESISTANCE
+        } else {
+            EMPTY_CELL_RESISTANCE
+        }
+    };
+    let on_first = |cell: &Cell| borders(cell, player, width, height).0;
+    let on_last = |cell: &Cell| borders(cell, player, width, height).1;
+
+    // Only the cells reachable from the first border can carry current.
+    let mut index: Vec<Option<usize>> = vec![None; width * height];
+    let mut cells = Vec::new();
+    let mut q = VecDeque::new();
+    for y in 0..height {
+        for x in 0..width {
+            let cell = Cell::new(x, y);
+            if on_first(&cell) && is_open(&cell) {
+                index[y * width + x] = Some(cells.len());
+                cells.push(cell.clone());
+                q.push_back(cell);
+            }
+        }
+    }
+    while let Some(cell) = q.pop_front() {
+        for c in cell.get_neighbours(width, height).unwrap() {
+            if index[c.y * width + c.x].is_none() && is_open(&c) {
+                index[c.y * width + c.x] = Some(cells.len());
+                cells.push(c.clone());
+                q.push_back(c);
+            }
+        }
+    }
+    if !cells.iter().any(on_last) {
+        return PlayerEvaluation {
+            resistance: None,
+            heatmap,
+        };
+    }
+
+    // Potentials of the cells, the first border is at 1 and the last one at 0.
+    let n = cells.len();
+    let mut matrix = vec![0.0; n * (n + 1)];
+    for (i, cell) in cells.iter().enumerate() {
+        let r = resistance(cell);
+        if on_first(cell) {
+            matrix[i * (n + 1) + i] += 1.0 / r;
+            matrix[i * (n + 1) + n] += 1.0 / r;
+        }
+        if on_last(cell) {
+            matrix[i * (n + 1) + i] += 1.0 / r;
+        }
+        for c in cell.get_neighbours(width, height).unwrap() {
+            if let Some(j) = index[c.y * width + c.x] {
+                let conductance = 1.0 / (r + resistance(&c));
+                matrix[i * (n + 1) + i] += conductance;
+                matrix[i * (n + 1) + j] -= conductance;
+            }
+        }
+    }
+    let potentials = solve(matrix, n);
+
+    let mut total_current = 0.0;
+    for (i, cell) in cells.iter().enumerate() {
+        let r = resistance(cell);
+        let mut current = 0.0;
+        if on_first(cell) {
+            let inflow = (1.0 - potentials[i]) / r;
+            total_current += inflow;
+            current += abs(inflow);
+        }
+        if on_last(cell) {
+            current += abs(potentials[i] / r);
+        }
+        for c in cell.get_neighbours(width, height).unwrap() {
+            if let Some(j) = index[c.y * width + c.x] {
+                current += abs((potentials[i] - potentials[j]) / (r + resistance(&c)));
+            }
+        }
+        // Every unit of current both enters and leaves the cell.
+        heatmap[cell.y][cell.x] = current / 2.0;
+    }
+    PlayerEvaluation {
+        resistance: Some(1.0 / total_current),
+        heatmap,
+    }
+}
+
+/// Evaluates the position as two electrical circuits, one for each player.
+/// Lower resistance between a player's borders means a stronger position.
+pub fn evaluate(board: &Board) -> Evaluation {
+    let first_player = evaluate_player(board, 1);
+    let second_player = evaluate_player(board, 2);
+    let first_player_estimate = match (first_player.resistance, second_player.resistance) {
+        (Some(first), Some(second)) => second / (first + second),
+        (Some(_), None) => 1.0,
+        (None, Some(_)) => 0.0,
+        (None, None) => 0.5,
+    };
+    Evaluation {
+        first_player,
+        second_player,
+        first_player_estimate,
+    }
+}
+
+#[cfg(test)]
+mod eval_tests {
+    use alloc::vec;
+
+    use super::evaluate;
+    use crate::{board::Board, cell::Cell};
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_single_cell() {
+        let evaluation = evaluate(&Board::new(1, 1).unwrap());
+        assert_close(evaluation.first_player.resistance.unwrap(), 2.0);
+        assert_close(evaluation.second_player.resistance.unwrap(), 2.0);
+        assert_close(evaluation.first_player.heatmap[0][0], 0.5);
+        assert_eq!(evaluation.leader(), None);
+    }
+
+    #[test]
+    fn test_rectangular_board() {
+        // Two parallel paths of two resistors for the first player, a chain of
+        // four for the second.
+        let evaluation = evaluate(&Board::new(2, 1).unwrap());
+        assert_close(evaluation.first_player.resistance.unwrap(), 1.0);
+        assert_close(evaluation.second_player.resistance.unwrap(), 4.0);
+        assert_close(evaluation.first_player_estimate, 0.8);
+        assert_eq!(evaluation.leader(), Some(1));
+        assert_eq!(evaluation.first_player.heatmap, vec![vec![0.5, 0.5]]);
+        assert_eq!(evaluation.second_player.heatmap, vec![vec![0.25, 0.25]]);
+    }
+
+    #[test]
+    fn test_empty_board_is_even() {
+        let evaluation = evaluate(&Board::new(5, 5).unwrap());
+        assert_close(
+            evaluation.first_player.resistance.unwrap(),
+            evaluation.second_player.resistance.unwrap(),
+        );
+        assert_eq!(evaluation.leader(), None);
+        // The short diagonal carries more current than the corners.
+        let heatmap = &evaluation.first_player.heatmap;
+        assert!(heatmap[2][2] > heatmap[0][0]);
+        assert!(heatmap[2][2] > heatmap[4][4]);
+    }
+
+    #[test]
+    fn test_counter_changes_resistance() {
+        let empty = evaluate(&Board::new(5, 5).unwrap());
+        let mut board = Board::new(5, 5).unwrap();
+        board.set_cell(&Cell::new(2, 2), 1).unwrap();
+        let evaluation = evaluate(&board);
+        assert!(
+            evaluation.first_player.resistance.unwrap() < empty.first_player.resistance.unwrap()
+        );
+        assert!(
+            evaluation.second_player.resistance.unwrap() > empty.second_player.resistance.unwrap()
+        );
+        assert_eq!(evaluation.second_player.heatmap[2][2], 0.0);
+        assert_eq!(evaluation.leader(), Some(1));
+
+        board.set_cell(&Cell::new(2, 2), 2).unwrap();
+        assert_eq!(evaluate(&board).leader(), Some(2));
+    }
+
+    #[test]
+    fn test_cut_off_player() {
+        let mut board = Board::new(3, 3).unwrap();
+        for y in 0..3 {
+            board.set_cell(&Cell::new(1, y), 1).unwrap();
+        }
+        board.set_cell(&Cell::new(0, 1), 2).unwrap();
+        let evaluation = evaluate(&board);
+        assert!(evaluation.first_player.resistance.unwrap() < 0.01);
+        assert_eq!(evaluation.second_player.resistance, None);
+        assert_eq!(evaluation.second_player.heatmap, vec![vec![0.0; 3]; 3]);
+        assert_eq!(evaluation.first_player_estimate, 1.0);
+        assert_eq!(evaluation.leader(), Some(1));
+    }
+}
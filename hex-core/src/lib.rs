@@ -0,0 +1,30 @@
+//! Rules of Hex without any dependency on the blockchain: the board, cells and
+//! their notation, win detection, the swap rule, parsing of game records and
+//! evaluation of positions.
+//! Builds with `no_std` when the default `std` feature is disabled, `borsh` and
+//! `serde` features derive the serialization used by the contract. The `ai`
+//! module with an MCTS player for off-chain bots and the `solver` of small
+//! boards require `std`.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod ai;
+pub mod board;
+pub mod cell;
+pub mod error;
+pub mod eval;
+pub mod record;
+#[cfg(feature = "std")]
+pub mod solver;
+pub mod state;
+pub mod union_find;
+
+pub use board::{Board, MAX_FIELD_SIZE};
+pub use cell::{column_letters, Cell};
+pub use error::Error;
+pub use eval::{evaluate, Evaluation, PlayerEvaluation};
+pub use record::{parse_record, Play, Record};
+pub use state::GameState;
+pub use union_find::UnionFind;
@@ -0,0 +1,219 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::cell::Cell;
+
+pub const SGF_SWAP: &str = "swap-pieces";
+pub const SGF_RESIGN: &str = "resign";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Play {
+    Place(Cell),
+    Swap,
+}
+
+impl Play {
+    pub fn to_notation(&self) -> String {
+        match self {
+            Play::Place(cell) => cell.to_notation(),
+            Play::Swap => "swap".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub size: Option<(usize, usize)>,
+    // Counters placed before the first move, the `AB` and `AW` properties of SGF.
+    pub setup: Vec<(u8, Cell)>,
+    pub moves: Vec<(u8, Play)>,
+}
+
+pub fn parse_play(value: &str) -> Result<Play, String> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        SGF_SWAP | "swap" => Ok(Play::Swap),
+        SGF_RESIGN => Err("Position can't contain resignation.".to_string()),
+        "" => Err("Position can't contain passes.".to_string()),
+        notation => Cell::from_notation(notation)
+            .map(Play::Place)
+            .ok_or(format!("Incorrect move notation: {}", notation)),
+    }
+}
+
+fn parse_setup(value: &str) -> Result<Cell, String> {
+    let notation = value.trim().to_ascii_lowercase();
+    Cell::from_notation(&notation).ok_or(format!("Incorrect setup notation: {}", notation))
+}
+
+pub fn parse_size(value: &str) -> Result<(usize, usize), String> {
+    let parse = |x: &str| {
+        x.trim()
+            .parse::<usize>()
+            .map_err(|_| format!("Incorrect board size: {}", value))
+    };
+    match value.split_once(':') {
+        Some((width, height)) => Ok((parse(width)?, parse(height)?)),
+        None => parse(value).map(|size| (size, size)),
+    }
+}
+
+// Only the main line of the record is supported, properties other than the
+// board size, the setup counters and the moves are ignored.
+fn parse_sgf(sgf: &str) -> Result<Record, String> {
+    let mut record = Record {
+        size: None,
+        setup: Vec::new(),
+        moves: Vec::new(),
+    };
+    let mut chars = sgf.trim().chars().peekable();
+    let mut identifier = String::new();
+    let mut depth = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => {
+                depth += 1;
+                if depth > 1 {
+                    return Err("Variations aren't supported.".to_string());
+                }
+            }
+            ')' => depth -= 1,
+            '[' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') => value.extend(chars.next()),
+                        Some(']') => break,
+                        Some(c) => value.push(c),
+                        None => return Err("Unterminated property value.".to_string()),
+                    }
+                }
+                match identifier.as_str() {
+                    "SZ" => record.size = Some(parse_size(&value)?),
+                    "AB" => record.setup.push((1, parse_setup(&value)?)),
+                    "AW" => record.setup.push((2, parse_setup(&value)?)),
+                    "B" => record.moves.push((1, parse_play(&value)?)),
+                    "W" => record.moves.push((2, parse_play(&value)?)),
+                    _ => {}
+                }
+                // Several values of one property follow each other without the identifier.
+                if chars.peek() != Some(&'[') {
+                    identifier.clear();
+                }
+            }
+            c if c.is_ascii_uppercase() => identifier.push(c),
+            ';' => identifier.clear(),
+            c if c.is_whitespace() => {}
+            c => return Err(format!("Unexpected character in SGF: {}", c)),
+        }
+    }
+    if depth != 0 {
+        return Err("Unbalanced parentheses in SGF.".to_string());
+    }
+    Ok(record)
+}
+
+/// Parses an SGF record or a list of moves in notation separated by spaces,
+/// commas or semicolons. The swap is always made by the second player, other
+/// moves of a list alternate.
+pub fn parse_record(record: &str) -> Result<Record, String> {
+    if record.trim_start().starts_with('(') {
+        return parse_sgf(record);
+    }
+    let mut moves = Vec::new();
+    for value in record
+        .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+        .filter(|x| !x.is_empty())
+    {
+        let play = parse_play(value)?;
+        let player = if play == Play::Swap {
+            2
+        } else {
+            (moves.len() % 2) as u8 + 1
+        };
+        moves.push((player, play));
+    }
+    Ok(Record {
+        size: None,
+        setup: Vec::new(),
+        moves,
+    })
+}
+
+#[cfg(test)]
+mod record_tests {
+    use alloc::{string::ToString, vec};
+
+    use super::{parse_play, parse_record, parse_size, Play};
+    use crate::cell::Cell;
+
+    #[test]
+    fn test_play_notation() {
+        for value in ["b3", "aa12", "swap"] {
+            assert_eq!(parse_play(value).unwrap().to_notation(), value);
+        }
+        assert_eq!(parse_play(" SWAP-PIECES "), Ok(Play::Swap));
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("11"), Ok((11, 11)));
+        assert_eq!(parse_size(" 7 : 5 "), Ok((7, 5)));
+        assert!(parse_size("7:").is_err());
+    }
+
+    #[test]
+    fn test_parse_notation_list() {
+        let record = parse_record("f6 SWAP, a11;b2").unwrap();
+        assert_eq!(record.size, None);
+        assert_eq!(record.setup, vec![]);
+        assert_eq!(
+            record.moves,
+            vec![
+                (1, Play::Place(Cell::new(5, 5))),
+                (2, Play::Swap),
+                (1, Play::Place(Cell::new(0, 10))),
+                (2, Play::Place(Cell::new(1, 1))),
+            ]
+        );
+        assert_eq!(parse_record(" ").unwrap().moves, vec![]);
+        assert!(parse_record("a1 resign").is_err());
+        assert!(parse_record("a1 zz").is_err());
+    }
+
+    #[test]
+    fn test_parse_sgf() {
+        let record =
+            parse_record("(;FF[4]GM[11]SZ[7:5]C[a [b\\]];B[a1];W[swap-pieces];B[c3])").unwrap();
+        assert_eq!(record.size, Some((7, 5)));
+        assert_eq!(
+            record.moves,
+            vec![
+                (1, Play::Place(Cell::new(0, 0))),
+                (2, Play::Swap),
+                (1, Play::Place(Cell::new(2, 2))),
+            ]
+        );
+        let record = parse_record("(;SZ[5]AB[b2][ d4 ]AW[c3];W[a1])").unwrap();
+        assert_eq!(
+            record.setup,
+            vec![
+                (1, Cell::new(1, 1)),
+                (1, Cell::new(3, 3)),
+                (2, Cell::new(2, 2)),
+            ]
+        );
+        assert_eq!(record.moves, vec![(2, Play::Place(Cell::new(0, 0)))]);
+        assert!(parse_record("(;SZ[5]AB[swap])").is_err());
+        assert!(parse_record("(;SZ[5];B[a1](;W[b2])(;W[c2]))").is_err());
+        assert!(parse_record("(;SZ[5];B[a1]").is_err());
+        assert!(parse_record("(;SZ[5];B[a1];W[resign])").is_err());
+        assert_eq!(
+            parse_record("(;SZ[5];B[a1];W[];B[b2])"),
+            Err("Position can't contain passes.".to_string())
+        );
+        assert!(parse_record("(;SZ[5];B[a1];W[b2]!)").is_err());
+    }
+}
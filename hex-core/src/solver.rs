@@ -0,0 +1,461 @@
+use std::collections::HashMap;
+
+use crate::board::Board;
+use crate::cell::Cell;
+use crate::error::Error;
+use crate::eval::evaluate;
+use crate::record::Play;
+use crate::state::GameState;
+
+const WIN: i32 = 10_000;
+const INFINITY: i32 = WIN + 1;
+// Below this depth ordering the moves by the evaluation costs more than it saves.
+const ORDERING_DEPTH: u16 = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolverConfig {
+    // The search gives up after visiting so many positions.
+    pub max_nodes: u64,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self {
+            max_nodes: 10_000_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    pub winner: u8,
+    // Plies until the connection when both players play the best moves: the
+    // winner wins as fast as possible, the loser resists as long as possible.
+    pub plies: usize,
+    // The fastest winning move or the longest defence for the player to move,
+    // `None` when the game is already finished.
+    pub best_move: Option<Play>,
+    pub nodes: u64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+struct Entry {
+    depth: u16,
+    // Relative to the position, not to the root of the search.
+    score: i32,
+    bound: Bound,
+    best: Option<Play>,
+}
+
+// A symmetry of the board. Both the 180-degree rotation and the reflection
+// `Cell::symm`, which also swaps the colours and the player to move, are
+// involutions and commute, so every transform is its own inverse.
+#[derive(Clone, Copy)]
+struct Transform {
+    rotate: bool,
+    reflect: bool,
+}
+
+impl Transform {
+    fn cell(&self, cell: &Cell, width: usize, height: usize) -> Cell {
+        let cell = if self.rotate {
+            Cell::new(width - 1 - cell.x, height - 1 - cell.y)
+        } else {
+            cell.clone()
+        };
+        if self.reflect {
+            cell.symm()
+        } else {
+            cell
+        }
+    }
+
+    fn play(&self, play: &Play, width: usize, height: usize) -> Play {
+        match play {
+            Play::Place(cell) => Play::Place(self.cell(cell, width, height)),
+            Play::Swap => Play::Swap,
+        }
+    }
+
+    fn board(&self, board: &Board) -> Board {
+        let (width, height) = if self.reflect {
+            (board.height, board.width)
+        } else {
+            (board.width, board.height)
+        };
+        let mut result = Board::new(width, height).unwrap();
+        for y in 0..board.height {
+            for x in 0..board.width {
+                let cell = Cell::new(x, y);
+                let value = match board.get_cell_unchecked(&cell) {
+                    color @ (1 | 2) if self.reflect => 3 - color,
+                    color => color,
+                };
+                result
+                    .set_cell(&self.cell(&cell, board.width, board.height), value)
+                    .unwrap();
+            }
+        }
+        result
+    }
+}
+
+// The field, the player to move and whether the swap is available. The last
+// one tells the reflection of a first move from a position after the swap.
+type Key = (Vec<u8>, u8, bool);
+
+struct Solver {
+    table: HashMap<Key, Entry>,
+    nodes: u64,
+    max_nodes: u64,
+}
+
+impl Solver {
+    // The smallest of the symmetric positions, with the transform leading to it.
+    fn canonical(&self, state: &GameState) -> (Key, Transform) {
+        let player = state.current_player();
+        let swap = state.turn == 1;
+        let mut best = (
+            (state.board.field.clone(), player, swap),
+            Transform {
+                rotate: false,
+                reflect: false,
+            },
+        );
+        for (rotate, reflect) in [(true, false), (false, true), (true, true)] {
+            if reflect && !state.board.is_square() {
+                continue;
+            }
+            let transform = Transform { rotate, reflect };
+            let player = if reflect { 3 - player } else { player };
+            let key = (transform.board(&state.board).field, player, swap);
+            if key < best.0 {
+                best = (key, transform);
+            }
+        }
+        best
+    }
+
+    fn ordered_moves(&self, state: &GameState, depth: u16, first: Option<Play>) -> Vec<Play> {
+        let mut moves = state.legal_moves();
+        // Any other move lets the opponent connect on the next ply.
+        let threats = state.winning_cells(3 - state.current_player());
+        if !threats.is_empty() {
+            moves.retain(|play| match play {
+                Play::Place(cell) => threats.contains(cell),
+                Play::Swap => true,
+            });
+        }
+        if depth >= ORDERING_DEPTH {
+            // Cells critical for both players go first.
+            let evaluation = evaluate(&state.board);
+            let weight = |play: &Play| match play {
+                Play::Place(cell) => {
+                    evaluation.first_player.heatmap[cell.y][cell.x]
+                        + evaluation.second_player.heatmap[cell.y][cell.x]
+                }
+                Play::Swap => f64::INFINITY,
+            };
+            moves.sort_by(|a, b| weight(b).total_cmp(&weight(a)));
+        }
+        if let Some(first) = first {
+            if let Some(i) = moves.iter().position(|play| *play == first) {
+                let play = moves.remove(i);
+                moves.insert(0, play);
+            }
+        }
+        moves
+    }
+
+    // Negamax with alpha-beta pruning. Scores are from the point of view of
+    // the player to move: `WIN - p` is a win at the ply `p` from the root,
+    // `p - WIN` a loss and 0 is unknown within the depth. `None` means that
+    // the node budget is exhausted.
+    fn search(
+        &mut self,
+        state: &GameState,
+        depth: u16,
+        ply: i32,
+        mut alpha: i32,
+        mut beta: i32,
+    ) -> Result<Option<i32>, Error> {
+        self.nodes += 1;
+        if self.nodes > self.max_nodes {
+            return Ok(None);
+        }
+        if state.is_finished() {
+            // The previous move connected the opponent's borders.
+            return Ok(Some(ply - WIN));
+        }
+        if depth == 0 {
+            return Ok(Some(0));
+        }
+        let player = state.current_player();
+        if !state.winning_cells(player).is_empty() {
+            return Ok(Some(WIN - ply - 1));
+        }
+
+        let (key, transform) = self.canonical(state);
+        let (width, height) = (state.board.width, state.board.height);
+        let mut first = None;
+        if let Some(entry) = self.table.get(&key) {
+            if entry.depth >= depth {
+                let score = from_table(entry.score, ply);
+                match entry.bound {
+                    Bound::Exact => return Ok(Some(score)),
+                    Bound::Lower => alpha = alpha.max(score),
+                    Bound::Upper => beta = beta.min(score),
+                }
+                if alpha >= beta {
+                    return Ok(Some(score));
+                }
+            }
+            first = entry
+                .best
+                .as_ref()
+                .map(|play| transform.play(play, width, height));
+        }
+
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        for play in self.ordered_moves(state, depth, first) {
+            let mut child = state.clone();
+            child.play(&play)?;
+            let score = match self.search(&child, depth - 1, ply + 1, -beta, -alpha)? {
+                Some(score) => -score,
+                None => return Ok(None),
+            };
+            if score > best_score {
+                best_score = score;
+                best_move = Some(play);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best_score <= original_alpha {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        // The key is canonical, so the reflection of the best move is stored.
+        let best = best_move.map(|play| transform.play(&play, width, height));
+        self.table.insert(
+            key,
+            Entry {
+                depth,
+                score: to_table(best_score, ply),
+                bound,
+                best,
+            },
+        );
+        Ok(Some(best_score))
+    }
+}
+
+fn to_table(score: i32, ply: i32) -> i32 {
+    match score {
+        s if s > 0 => s + ply,
+        s if s < 0 => s - ply,
+        _ => 0,
+    }
+}
+
+fn from_table(score: i32, ply: i32) -> i32 {
+    match score {
+        s if s > 0 => s - ply,
+        s if s < 0 => s + ply,
+        _ => 0,
+    }
+}
+
+/// Proves the winner of the position with iterative deepening alpha-beta
+/// search. Practical for boards up to 4x4, 5x5 after the first moves and 6x6
+/// or 7x7 in the middle game, returns `None` when `max_nodes` is exhausted.
+pub fn solve(state: &GameState, config: &SolverConfig) -> Result<Option<Solution>, Error> {
+    let player = state.current_player();
+    if let Some(winner) = state.winner {
+        return Ok(Some(Solution {
+            winner,
+            plies: 0,
+            best_move: None,
+            nodes: 0,
+        }));
+    }
+
+    if let Some(cell) = state.winning_cells(player).into_iter().next() {
+        return Ok(Some(Solution {
+            winner: player,
+            plies: 1,
+            best_move: Some(Play::Place(cell)),
+            nodes: 1,
+        }));
+    }
+
+    let mut solver = Solver {
+        table: HashMap::new(),
+        nodes: 0,
+        max_nodes: config.max_nodes,
+    };
+    // A full board always has a winner, the swap takes one more ply. At most
+    // `MAX_FIELD_SIZE * MAX_FIELD_SIZE + 1` plies, which fits a `u16`.
+    let max_depth = (state.legal_moves().len() + 1) as u16;
+    for depth in 1..=max_depth {
+        let score = match solver.search(state, depth, 0, -INFINITY, INFINITY)? {
+            Some(score) => score,
+            None => return Ok(None),
+        };
+        if score == 0 {
+            continue;
+        }
+        let (key, transform) = solver.canonical(state);
+        let (width, height) = (state.board.width, state.board.height);
+        let best_move = solver.table[&key]
+            .best
+            .as_ref()
+            .map(|play| transform.play(play, width, height));
+        return Ok(Some(Solution {
+            winner: if score > 0 { player } else { 3 - player },
+            plies: (WIN - score.abs()) as usize,
+            best_move,
+            nodes: solver.nodes,
+        }));
+    }
+    unreachable!("Hex position without a winner on a full board")
+}
+
+#[cfg(test)]
+mod solver_tests {
+    use super::{solve, Solution, SolverConfig, Transform};
+    use crate::{board::Board, cell::Cell, record::Play, state::GameState};
+
+    fn solved(state: &GameState) -> Solution {
+        solve(state, &SolverConfig::default()).unwrap().unwrap()
+    }
+
+    fn play(state: &mut GameState, moves: &[&str]) {
+        for notation in moves {
+            let cell = Cell::from_notation(notation).unwrap();
+            state.place(&cell, state.current_player()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_transform() {
+        let mut board = Board::new(3, 2).unwrap();
+        board.set_cell(&Cell::new(0, 0), 1).unwrap();
+        board.set_cell(&Cell::new(2, 0), 2).unwrap();
+        let rotate = Transform {
+            rotate: true,
+            reflect: false,
+        };
+        let rotated = rotate.board(&board);
+        assert_eq!(rotated.get_cell(&Cell::new(2, 1)), Ok(1));
+        assert_eq!(rotated.get_cell(&Cell::new(0, 1)), Ok(2));
+        assert_eq!(rotate.board(&rotated), board);
+
+        let both = Transform {
+            rotate: true,
+            reflect: true,
+        };
+        let transformed = both.board(&board);
+        assert_eq!((transformed.width, transformed.height), (2, 3));
+        assert_eq!(transformed.get_cell(&Cell::new(1, 2)), Ok(2));
+        assert_eq!(transformed.get_cell(&Cell::new(1, 0)), Ok(1));
+        assert_eq!(both.board(&transformed), board);
+        assert_eq!(
+            both.cell(&both.cell(&Cell::new(2, 1), 3, 2), 2, 3),
+            Cell::new(2, 1)
+        );
+    }
+
+    #[test]
+    fn test_finished_position() {
+        let mut state = GameState::new(2, 2).unwrap();
+        play(&mut state, &["a1", "b1", "a2"]);
+        assert_eq!(
+            solved(&state),
+            Solution {
+                winner: 1,
+                plies: 0,
+                best_move: None,
+                nodes: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_win_in_one() {
+        let mut state = GameState::new(3, 3).unwrap();
+        play(&mut state, &["a1", "c1", "a2", "c3"]);
+        let solution = solved(&state);
+        assert_eq!(solution.winner, 1);
+        assert_eq!(solution.plies, 1);
+        assert_eq!(solution.best_move, Some(Play::Place(Cell::new(0, 2))));
+    }
+
+    #[test]
+    fn test_lost_position() {
+        // a3 and b1 form a bridge, the second player can't cut it.
+        let mut state = GameState::new(3, 3).unwrap();
+        play(&mut state, &["b1", "c3", "a3"]);
+        let solution = solved(&state);
+        assert_eq!(solution.winner, 1);
+        assert_eq!(solution.plies, 2);
+        assert!(solution.best_move.is_some());
+
+        // The second player blocks a3 and gets two threats of their own.
+        let mut state = GameState::new(3, 3).unwrap();
+        play(&mut state, &["a2", "c1", "b1", "c2", "c3"]);
+        let solution = solved(&state);
+        assert_eq!(solution.winner, 2);
+        assert_eq!(solution.plies, 3);
+        assert_eq!(solution.best_move, Some(Play::Place(Cell::new(0, 2))));
+    }
+
+    #[test]
+    fn test_small_boards() {
+        // The swap rule makes every opening of a square board lose.
+        assert_eq!(solved(&GameState::new(1, 1).unwrap()).winner, 1);
+        for size in 2..=4 {
+            assert_eq!(solved(&GameState::new(size, size).unwrap()).winner, 2);
+        }
+        // The player whose borders are closer wins even moving second.
+        assert_eq!(solved(&GameState::new(3, 4).unwrap()).winner, 2);
+        assert_eq!(solved(&GameState::new(4, 3).unwrap()).winner, 1);
+    }
+
+    #[test]
+    fn test_swap() {
+        // The center of 3x3 wins, so the second player swaps it.
+        let mut state = GameState::new(3, 3).unwrap();
+        play(&mut state, &["b2"]);
+        let solution = solved(&state);
+        assert_eq!(solution.winner, 2);
+        assert_eq!(solution.best_move, Some(Play::Swap));
+
+        // A corner loses, so there is no need to swap it.
+        let mut state = GameState::new(3, 3).unwrap();
+        play(&mut state, &["a1"]);
+        let solution = solved(&state);
+        assert_eq!(solution.winner, 2);
+        assert_ne!(solution.best_move, Some(Play::Swap));
+    }
+
+    #[test]
+    fn test_node_budget() {
+        let state = GameState::new(5, 5).unwrap();
+        assert_eq!(solve(&state, &SolverConfig { max_nodes: 100 }), Ok(None));
+    }
+}
@@ -0,0 +1,757 @@
+use alloc::{collections::VecDeque, vec, vec::Vec};
+
+use crate::board::Board;
+use crate::cell::Cell;
+use crate::error::Error;
+use crate::record::Play;
+use crate::union_find::UnionFind;
+
+// Position of a game together with the connectivity of the counters. Only the
+// board and the turn are written to JSON, the rest is derived from them.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameState {
+    pub board: Board,
+    pub turn: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub connectivity: UnionFind,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub winner: Option<u8>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub winning_path: Option<Vec<Cell>>,
+}
+
+impl GameState {
+    pub fn new(width: usize, height: usize) -> Result<Self, Error> {
+        Ok(Self {
+            board: Board::new(width, height)?,
+            turn: 0,
+            connectivity: UnionFind::new(width * height + 4),
+            winner: None,
+            winning_path: None,
+        })
+    }
+
+    // Rebuilds the connectivity of a position where only the board and the turn
+    // are known, like the `get_game` view of the contract.
+    pub fn from_board(board: Board, turn: usize) -> Result<Self, Error> {
+        let mut state = Self::new(board.width, board.height)?;
+        if board.field.len() != state.board.field.len() {
+            return Err(Error::CellOutOfBounds);
+        }
+        state.board = board;
+        state.turn = turn;
+        for y in 0..state.board.height {
+            for x in 0..state.board.width {
+                match state.board.get_cell_unchecked(&Cell::new(x, y)) {
+                    0 => {}
+                    1 | 2 => state.process_cell(&Cell::new(x, y))?,
+                    _ => return Err(Error::IncorrectCellValue),
+                }
+            }
+        }
+        Ok(state)
+    }
+
+    pub fn current_player(&self) -> u8 {
+        (self.turn % 2) as u8 + 1
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.winner.is_some()
+    }
+
+    pub fn place(&mut self, cell: &Cell, player: u8) -> Result<(), Error> {
+        if self.is_finished() {
+            return Err(Error::GameFinished);
+        }
+        if self.board.get_cell(cell)? != 0 {
+            return Err(Error::CellOccupied);
+        }
+        if self.current_player() != player {
+            return Err(Error::NotYourTurn);
+        }
+        self.board.set_cell(cell, player)?;
+        self.turn += 1;
+        self.process_cell(cell)
+    }
+
+    // Returns the cell of the counter before it was reflected.
+    pub fn swap(&mut self) -> Result<Cell, Error> {
+        if self.turn != 1 {
+            return Err(Error::SwapNotAllowed);
+        }
+        // Reflection of a counter isn't a symmetry of a rectangular board.
+        if !self.board.is_square() {
+            return Err(Error::SwapOnRectangularBoard);
+        }
+
+        let non_zero_byte = self
+            .board
+            .field
+            .iter()
+            .enumerate()
+            .find(|(_, &x)| x != 0)
+            .unwrap();
+        let mut bit_number = 8 * non_zero_byte.0;
+        if non_zero_byte.1 & 4 == 4 {
+            bit_number += 2;
+        } else if non_zero_byte.1 & 16 == 16 {
+            bit_number += 4;
+        } else if non_zero_byte.1 & 64 == 64 {
+            bit_number += 6;
+        }
+        let cell = self.board.get_coords(bit_number);
+
+        self.board.set_cell(&cell, 0)?;
+        self.board.set_cell(&cell.symm(), 2)?;
+        self.turn += 1;
+        // There is only one counter on the board when swap is applied,
+        // so connectivity can be built from scratch.
+        self.connectivity = UnionFind::new(self.connectivity.len());
+        self.process_cell(&cell.symm())?;
+        Ok(cell)
+    }
+
+    pub fn play(&mut self, play: &Play) -> Result<(), Error> {
+        match play {
+            Play::Place(cell) => self.place(cell, self.current_player()),
+            Play::Swap => self.swap().map(|_| ()),
+        }
+    }
+
+    // Every empty cell, and the swap on the second player's first turn on a
+    // square board.
+    pub fn legal_moves(&self) -> Vec<Play> {
+        let mut moves = Vec::new();
+        if self.is_finished() {
+            return moves;
+        }
+        if self.turn == 1 && self.board.is_square() {
+            moves.push(Play::Swap);
+        }
+        for y in 0..self.board.height {
+            for x in 0..self.board.width {
+                let cell = Cell::new(x, y);
+                if self.board.get_cell_unchecked(&cell) == 0 {
+                    moves.push(Play::Place(cell));
+                }
+            }
+        }
+        moves
+    }
+
+    // Empty cells where a counter of the player would connect their borders.
+    pub fn winning_cells(&self, player: u8) -> Vec<Cell> {
+        let (width, height) = (self.board.width, self.board.height);
+        let mut connectivity = self.connectivity.clone();
+        let (border1, border2) = self.border_nodes(player);
+        let (first, last) = (connectivity.find(border1), connectivity.find(border2));
+        let mut cells = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let cell = Cell::new(x, y);
+                if self.board.get_cell_unchecked(&cell) != 0 {
+                    continue;
+                }
+                let (coordinate, length) = if player == 1 { (y, height) } else { (x, width) };
+                let mut touches_first = coordinate == 0;
+                let mut touches_last = coordinate + 1 == length;
+                for c in cell.get_neighbours(width, height).unwrap() {
+                    if self.board.get_cell_unchecked(&c) == player {
+                        let root = connectivity.find(self.cell_node(&c));
+                        touches_first |= root == first;
+                        touches_last |= root == last;
+                    }
+                }
+                if touches_first && touches_last {
+                    cells.push(cell);
+                }
+            }
+        }
+        cells
+    }
+
+    fn cell_node(&self, cell: &Cell) -> usize {
+        cell.y * self.board.width + cell.x
+    }
+
+    fn border_nodes(&self, color: u8) -> (usize, usize) {
+        let cells_count = self.board.width * self.board.height;
+        if color == 1 {
+            (cells_count, cells_count + 1)
+        } else {
+            (cells_count + 2, cells_count + 3)
+        }
+    }
+
+    fn process_cell(&mut self, cell: &Cell) -> Result<(), Error> {
+        let color = self.board.get_cell(cell)?;
+        let (width, height) = (self.board.width, self.board.height);
+        let node = self.cell_node(cell);
+        let (border1, border2) = self.border_nodes(color);
+
+        let (coordinate, length) = if color == 1 {
+            (cell.y, height)
+        } else {
+            (cell.x, width)
+        };
+        if coordinate == 0 {
+            self.connectivity.union(node, border1);
+        }
+        if coordinate + 1 == length {
+            self.connectivity.union(node, border2);
+        }
+        for c in cell.get_neighbours(width, height)? {
+            if self.board.get_cell_unchecked(&c) == color {
+                let neighbour = self.cell_node(&c);
+                self.connectivity.union(node, neighbour);
+            }
+        }
+
+        if self.connectivity.connected(border1, border2) {
+            self.winner = Some(color);
+            self.winning_path = self.find_winning_path(color)?;
+        }
+        Ok(())
+    }
+
+    fn find_winning_path(&self, color: u8) -> Result<Option<Vec<Cell>>, Error> {
+        let (width, height) = (self.board.width, self.board.height);
+        let is_start = |c: &Cell| if color == 1 { c.y == 0 } else { c.x == 0 };
+        let is_finish = |c: &Cell| {
+            if color == 1 {
+                c.y + 1 == height
+            } else {
+                c.x + 1 == width
+            }
+        };
+
+        let mut parent: Vec<Option<usize>> = vec![None; width * height];
+        let mut visited = vec![false; width * height];
+        let mut q: VecDeque<Cell> = VecDeque::new();
+        let border_len = if color == 1 { width } else { height };
+        for i in 0..border_len {
+            let cell = if color == 1 {
+                Cell::new(i, 0)
+            } else {
+                Cell::new(0, i)
+            };
+            if self.board.get_cell_unchecked(&cell) == color {
+                visited[cell.y * width + cell.x] = true;
+                q.push_back(cell);
+            }
+        }
+
+        while let Some(v) = q.pop_front() {
+            if is_finish(&v) {
+                let mut path = vec![v.clone()];
+                let mut index = v.y * width + v.x;
+                while let Some(prev) = parent[index] {
+                    path.push(Cell::new(prev % width, prev / width));
+                    index = prev;
+                }
+                path.reverse();
+                debug_assert!(is_start(&path[0]));
+                return Ok(Some(path));
+            }
+            for c in v.get_neighbours(width, height)? {
+                let index = c.y * width + c.x;
+                if !visited[index] && self.board.get_cell_unchecked(&c) == color {
+                    visited[index] = true;
+                    parent[index] = Some(v.y * width + v.x);
+                    q.push_back(c);
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod state_tests {
+    use alloc::{collections::VecDeque, vec, vec::Vec};
+
+    use super::GameState;
+    use crate::{board::Board, cell::Cell, error::Error, record::Play, union_find::UnionFind};
+
+    fn place(state: &mut GameState, x: usize, y: usize, color: u8) {
+        state.board.set_cell(&Cell::new(x, y), color).unwrap();
+        state.process_cell(&Cell::new(x, y)).unwrap();
+    }
+
+    fn connected(state: &mut GameState, a: usize, b: usize) -> bool {
+        state.connectivity.connected(a, b)
+    }
+
+    #[test]
+    fn test_place_wrong_player_1() {
+        let mut state = GameState::new(11, 11).unwrap();
+        assert_eq!(state.place(&Cell::new(1, 1), 2), Err(Error::NotYourTurn));
+    }
+
+    #[test]
+    fn test_place_wrong_player_2() {
+        let mut state = GameState::new(11, 11).unwrap();
+        state.place(&Cell::new(1, 1), 1).unwrap();
+        assert_eq!(state.place(&Cell::new(2, 1), 1), Err(Error::NotYourTurn));
+    }
+
+    #[test]
+    fn test_place_cell_is_already_filled() {
+        let mut state = GameState::new(11, 11).unwrap();
+        state.place(&Cell::new(1, 1), 1).unwrap();
+        assert_eq!(state.place(&Cell::new(1, 1), 2), Err(Error::CellOccupied));
+    }
+
+    #[test]
+    fn test_place_out_of_bounds() {
+        let mut state = GameState::new(5, 3).unwrap();
+        assert_eq!(
+            state.place(&Cell::new(1, 3), 1),
+            Err(Error::CellOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_place() {
+        let mut state = GameState::new(11, 11).unwrap();
+        state.place(&Cell::new(1, 1), 1).unwrap();
+        state.place(&Cell::new(1, 2), 2).unwrap();
+        state.place(&Cell::new(10, 7), 1).unwrap();
+        state.place(&Cell::new(5, 9), 2).unwrap();
+        state.place(&Cell::new(3, 7), 1).unwrap();
+
+        assert_eq!(state.turn, 5);
+        assert_eq!(state.current_player(), 2);
+        for i in 0..11 {
+            for j in 0..11 {
+                let cell = Cell::new(i, j);
+                if cell == Cell::new(1, 1) || cell == Cell::new(10, 7) || cell == Cell::new(3, 7) {
+                    assert_eq!(state.board.get_cell(&cell), Ok(1));
+                } else if cell == Cell::new(1, 2) || cell == Cell::new(5, 9) {
+                    assert_eq!(state.board.get_cell(&cell), Ok(2));
+                } else {
+                    assert_eq!(state.board.get_cell(&cell), Ok(0));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_board() {
+        let mut state = GameState::new(4, 3).unwrap();
+        for (x, y, color) in [(1, 0, 1), (0, 2, 2), (1, 1, 1), (2, 1, 2)] {
+            state.place(&Cell::new(x, y), color).unwrap();
+        }
+        assert_eq!(
+            GameState::from_board(state.board.clone(), state.turn),
+            Ok(state.clone())
+        );
+
+        state.place(&Cell::new(1, 2), 1).unwrap();
+        let restored = GameState::from_board(state.board.clone(), state.turn).unwrap();
+        assert_eq!(restored.winner, Some(1));
+        assert_eq!(restored.winning_path, state.winning_path);
+
+        let mut board = Board::new(2, 2).unwrap();
+        board.field[0] = 3;
+        assert_eq!(
+            GameState::from_board(board, 1),
+            Err(Error::IncorrectCellValue)
+        );
+        let mut board = Board::new(2, 2).unwrap();
+        board.field.push(0);
+        assert_eq!(GameState::from_board(board, 0), Err(Error::CellOutOfBounds));
+    }
+
+    #[test]
+    fn test_legal_moves() {
+        let mut state = GameState::new(3, 3).unwrap();
+        assert_eq!(state.legal_moves().len(), 9);
+        state.place(&Cell::new(1, 1), 1).unwrap();
+        let moves = state.legal_moves();
+        assert_eq!(moves.len(), 9);
+        assert_eq!(moves[0], Play::Swap);
+        assert!(!moves.contains(&Play::Place(Cell::new(1, 1))));
+
+        let mut state = GameState::new(3, 2).unwrap();
+        state.place(&Cell::new(1, 1), 1).unwrap();
+        assert!(!state.legal_moves().contains(&Play::Swap));
+    }
+
+    #[test]
+    fn test_winning_cells() {
+        let mut state = GameState::new(3, 3).unwrap();
+        assert_eq!(state.winning_cells(1), vec![]);
+        state.place(&Cell::new(1, 0), 1).unwrap();
+        state.place(&Cell::new(2, 2), 2).unwrap();
+        state.place(&Cell::new(0, 2), 1).unwrap();
+        assert_eq!(
+            state.winning_cells(1),
+            vec![Cell::new(0, 1), Cell::new(1, 1)]
+        );
+        assert_eq!(state.winning_cells(2), vec![]);
+
+        let state = GameState::new(1, 2).unwrap();
+        assert_eq!(
+            state.winning_cells(2),
+            vec![Cell::new(0, 0), Cell::new(0, 1)]
+        );
+        assert_eq!(state.winning_cells(1), vec![]);
+    }
+
+    #[test]
+    fn test_swap_too_early() {
+        let mut state = GameState::new(11, 11).unwrap();
+        assert_eq!(state.swap(), Err(Error::SwapNotAllowed));
+    }
+
+    #[test]
+    fn test_swap_too_late() {
+        let mut state = GameState::new(11, 11).unwrap();
+        state.place(&Cell::new(2, 5), 1).unwrap();
+        state.place(&Cell::new(10, 7), 2).unwrap();
+        assert_eq!(state.swap(), Err(Error::SwapNotAllowed));
+    }
+
+    #[test]
+    fn test_swap() {
+        let mut state = GameState::new(11, 11).unwrap();
+        state.place(&Cell::new(10, 7), 1).unwrap();
+
+        let c = state.swap().unwrap();
+        assert_eq!(Cell::new(10, 7), c);
+        assert_eq!(Ok(0), state.board.get_cell(&Cell::new(10, 7)));
+        assert_eq!(Ok(2), state.board.get_cell(&Cell::new(7, 10)));
+
+        state.place(&Cell::new(1, 1), 1).unwrap();
+        state.place(&Cell::new(5, 9), 2).unwrap();
+        state.place(&Cell::new(3, 7), 1).unwrap();
+
+        assert_eq!(state.turn, 5);
+        for i in 0..11 {
+            for j in 0..11 {
+                let cell = Cell::new(i, j);
+                if cell == Cell::new(1, 1) || cell == Cell::new(3, 7) {
+                    assert_eq!(state.board.get_cell(&cell), Ok(1));
+                } else if cell == Cell::new(7, 10) || cell == Cell::new(5, 9) {
+                    assert_eq!(state.board.get_cell(&cell), Ok(2));
+                } else {
+                    assert_eq!(state.board.get_cell(&cell), Ok(0));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_swap_rectangular_board() {
+        let mut state = GameState::new(11, 9).unwrap();
+        state.place(&Cell::new(2, 3), 1).unwrap();
+        assert_eq!(state.swap(), Err(Error::SwapOnRectangularBoard));
+    }
+
+    #[test]
+    fn test_process_cell() {
+        let mut state = GameState::new(5, 5).unwrap();
+        let (top, bottom) = state.border_nodes(1);
+        let (left, right) = state.border_nodes(2);
+        let node = |x: usize, y: usize| y * 5 + x;
+
+        for &(x, y) in [(0, 1), (1, 2), (0, 2), (2, 1), (4, 4), (3, 4), (0, 3)].iter() {
+            place(&mut state, x, y, 1);
+        }
+        assert!(connected(&mut state, node(0, 1), node(2, 1)));
+        assert!(connected(&mut state, node(0, 1), node(0, 3)));
+        assert!(connected(&mut state, node(3, 4), bottom));
+        assert!(!connected(&mut state, node(0, 1), top));
+        assert!(!connected(&mut state, node(0, 1), bottom));
+        assert!(!connected(&mut state, node(0, 3), left));
+
+        place(&mut state, 0, 0, 1);
+        assert!(connected(&mut state, node(2, 1), top));
+
+        place(&mut state, 4, 0, 2);
+        place(&mut state, 3, 0, 2);
+        assert!(connected(&mut state, node(3, 0), right));
+        assert!(!connected(&mut state, node(3, 0), left));
+        assert!(!connected(&mut state, top, bottom));
+        assert!(!state.is_finished());
+
+        place(&mut state, 0, 4, 1);
+        assert!(connected(&mut state, top, bottom));
+        assert!(state.is_finished());
+        assert_eq!(state.winner, Some(1));
+    }
+
+    #[test]
+    fn test_place_and_swap_connectivity() {
+        let mut state = GameState::new(5, 5).unwrap();
+        let (top, bottom) = state.border_nodes(1);
+        let (left, right) = state.border_nodes(2);
+        let node = |x: usize, y: usize| y * 5 + x;
+        assert_eq!(state.connectivity, UnionFind::new(29));
+
+        state.place(&Cell::new(3, 0), 1).unwrap();
+        assert!(connected(&mut state, node(3, 0), top));
+
+        state.swap().unwrap();
+        assert!(!connected(&mut state, node(3, 0), top));
+        assert!(connected(&mut state, node(0, 3), left));
+
+        state.place(&Cell::new(4, 4), 1).unwrap();
+        assert!(connected(&mut state, node(4, 4), bottom));
+
+        state.place(&Cell::new(1, 2), 2).unwrap();
+        assert!(connected(&mut state, node(1, 2), left));
+
+        state.place(&Cell::new(4, 2), 1).unwrap();
+        assert!(!connected(&mut state, node(4, 2), bottom));
+
+        state.place(&Cell::new(3, 2), 2).unwrap();
+        assert!(!connected(&mut state, node(3, 2), left));
+        assert!(!connected(&mut state, node(3, 2), right));
+
+        state.place(&Cell::new(4, 3), 1).unwrap();
+        assert!(connected(&mut state, node(4, 2), bottom));
+        assert!(!connected(&mut state, top, bottom));
+        assert!(!connected(&mut state, left, right));
+    }
+
+    #[test]
+    fn test_connection_winner() {
+        let mut state = GameState::new(2, 2).unwrap();
+        state.place(&Cell::new(0, 0), 1).unwrap();
+        state.place(&Cell::new(1, 0), 2).unwrap();
+        assert!(!state.is_finished());
+
+        state.place(&Cell::new(0, 1), 1).unwrap();
+        assert_eq!(state.winner, Some(1));
+        assert_eq!(
+            state.winning_path,
+            Some(vec![Cell::new(0, 0), Cell::new(0, 1)])
+        );
+        assert_eq!(state.place(&Cell::new(1, 1), 2), Err(Error::GameFinished));
+    }
+
+    #[test]
+    fn test_rectangular_connection_winner() {
+        let mut state = GameState::new(4, 2).unwrap();
+        state.place(&Cell::new(3, 0), 1).unwrap();
+        state.place(&Cell::new(0, 0), 2).unwrap();
+        state.place(&Cell::new(2, 1), 1).unwrap();
+        assert_eq!(state.winner, Some(1));
+        assert_eq!(
+            state.winning_path,
+            Some(vec![Cell::new(3, 0), Cell::new(2, 1)])
+        );
+    }
+
+    #[test]
+    fn test_large_board_connection_winner() {
+        let mut state = GameState::new(32, 32).unwrap();
+        for x in 0..31 {
+            place(&mut state, x, 31, 2);
+        }
+        assert!(!state.is_finished());
+        place(&mut state, 31, 31, 2);
+        assert_eq!(state.winner, Some(2));
+        assert_eq!(state.winning_path.unwrap().len(), 32);
+    }
+
+    #[test]
+    fn test_find_winning_path() {
+        let mut state = GameState::new(5, 5).unwrap();
+        assert_eq!(state.find_winning_path(2).unwrap(), None);
+
+        for (x, y) in [
+            (0, 2),
+            (1, 2),
+            (1, 1),
+            (2, 1),
+            (2, 2),
+            (3, 2),
+            (4, 1),
+            (3, 3),
+            (4, 2),
+        ] {
+            state.board.set_cell(&Cell::new(x, y), 2).unwrap();
+        }
+        state.board.set_cell(&Cell::new(0, 0), 1).unwrap();
+        assert_eq!(state.find_winning_path(1).unwrap(), None);
+        assert_eq!(
+            state.find_winning_path(2).unwrap(),
+            Some(vec![
+                Cell::new(0, 2),
+                Cell::new(1, 2),
+                Cell::new(2, 2),
+                Cell::new(3, 2),
+                Cell::new(4, 1),
+            ])
+        );
+    }
+
+    // The previous connectivity check, which relabels whole groups touching
+    // a border on the `data` board. Kept to compare both implementations.
+    struct LegacyConnectivity {
+        board: Board,
+        data: Board,
+        // Labels read and written and the colors read by the BFS.
+        accesses: core::cell::Cell<usize>,
+    }
+
+    impl LegacyConnectivity {
+        fn new(field_size: usize) -> Self {
+            Self {
+                board: Board::new(field_size, field_size).unwrap(),
+                data: Board::new(field_size, field_size).unwrap(),
+                accesses: core::cell::Cell::new(0),
+            }
+        }
+
+        fn color(&self, cell: &Cell) -> u8 {
+            self.accesses.set(self.accesses.get() + 1);
+            self.board.get_cell_unchecked(cell)
+        }
+
+        fn label(&self, cell: &Cell) -> u8 {
+            self.accesses.set(self.accesses.get() + 1);
+            self.data.get_cell_unchecked(cell)
+        }
+
+        fn set_label(&mut self, cell: &Cell, label: u8) {
+            self.accesses.set(self.accesses.get() + 1);
+            self.data.set_cell(cell, label).unwrap();
+        }
+
+        fn place(&mut self, cell: Cell, color: u8) -> bool {
+            self.board.set_cell(&cell, color).unwrap();
+            let (mut border1, mut border2) = if color == 1 {
+                (cell.y == 0, cell.y + 1 == self.data.height)
+            } else {
+                (cell.x == 0, cell.x + 1 == self.data.width)
+            };
+            let neighbours = cell
+                .get_neighbours(self.data.width, self.data.height)
+                .unwrap();
+            let good_neighbours = neighbours
+                .iter()
+                .filter(|c| self.board.get_cell_unchecked(c) == color);
+            border1 = border1 || good_neighbours.clone().any(|c| self.label(c) == 1);
+            border2 = border2 || good_neighbours.clone().any(|c| self.label(c) == 2);
+            if border1 && border2 {
+                true
+            } else if border1 {
+                self.bfs(cell, color, 1)
+            } else if border2 {
+                self.bfs(cell, color, 2)
+            } else {
+                false
+            }
+        }
+
+        fn bfs(&mut self, cell: Cell, color: u8, border: u8) -> bool {
+            self.set_label(&cell, border);
+            let mut q: VecDeque<Cell> = VecDeque::new();
+            q.push_back(cell);
+            while let Some(v) = q.pop_front() {
+                let good_neighbours: Vec<Cell> = v
+                    .get_neighbours(self.data.width, self.data.height)
+                    .unwrap()
+                    .into_iter()
+                    .filter(|c| self.color(c) == color && self.label(c) != border)
+                    .collect();
+                if good_neighbours.iter().any(|c| self.label(c) != 0) {
+                    return true;
+                }
+                for c in good_neighbours.into_iter() {
+                    self.set_label(&c, border);
+                    q.push_back(c);
+                }
+            }
+            false
+        }
+    }
+
+    fn shuffled_cells(field_size: usize, seed: u64) -> Vec<Cell> {
+        let mut cells: Vec<Cell> = (0..field_size * field_size)
+            .map(|i| Cell::new(i % field_size, i / field_size))
+            .collect();
+        let mut state = seed;
+        for i in (1..cells.len()).rev() {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            cells.swap(i, ((state >> 33) % (i as u64 + 1)) as usize);
+        }
+        cells
+    }
+
+    fn union_find_finish_ply(field_size: usize, cells: &[Cell]) -> usize {
+        let mut state = GameState::new(field_size, field_size).unwrap();
+        for (ply, cell) in cells.iter().enumerate() {
+            state.place(cell, (ply % 2 + 1) as u8).unwrap();
+            if state.is_finished() {
+                return ply;
+            }
+        }
+        unreachable!("Hex game can't end in a draw");
+    }
+
+    fn legacy_finish_ply(field_size: usize, cells: &[Cell]) -> usize {
+        let mut legacy = LegacyConnectivity::new(field_size);
+        for (ply, cell) in cells.iter().enumerate() {
+            if legacy.place(cell.clone(), (ply % 2 + 1) as u8) {
+                return ply;
+            }
+        }
+        unreachable!("Hex game can't end in a draw");
+    }
+
+    #[test]
+    fn test_union_find_matches_bfs() {
+        for seed in 0..20 {
+            let cells = shuffled_cells(7, seed);
+            assert_eq!(
+                union_find_finish_ply(7, &cells),
+                legacy_finish_ply(7, &cells)
+            );
+        }
+    }
+
+    // Every move is a transaction, so its gas is bounded by the most expensive
+    // move. That's mostly the connectivity check, counted as the parents or
+    // the labels and colors read and written, because the unit tests of the
+    // contract don't meter wasm instructions. Relabelling touches whole groups
+    // joining a border, the union-find only short paths to the roots.
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_union_find_cheaper_than_bfs() {
+        use crate::union_find::ACCESSES;
+
+        let field_size = crate::board::MAX_FIELD_SIZE;
+        for seed in 0..5 {
+            let cells = shuffled_cells(field_size, seed);
+            // The finishing move also looks for the winning path.
+            let ply = union_find_finish_ply(field_size, &cells);
+            let mut state = GameState::new(field_size, field_size).unwrap();
+            let mut legacy = LegacyConnectivity::new(field_size);
+            let (mut union_find_max, mut bfs_max) = (0, 0);
+            for (i, cell) in cells.iter().take(ply).enumerate() {
+                let color = (i % 2 + 1) as u8;
+                ACCESSES.with(|accesses| accesses.set(0));
+                state.place(cell, color).unwrap();
+                union_find_max = union_find_max.max(ACCESSES.with(|accesses| accesses.get()));
+
+                legacy.accesses.set(0);
+                legacy.place(cell.clone(), color);
+                bfs_max = bfs_max.max(legacy.accesses.get());
+            }
+            assert!(union_find_max * 4 < bfs_max);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+// Parents are packed with as many bits as the largest node needs, 7 bits on
+// 11x11 boards, so the stored state stays close to the size of the board.
+// Reads and writes of the parents by the current thread, the tests compare
+// them with the cells visited by the BFS relabelling the structure replaced.
+#[cfg(all(test, feature = "std"))]
+std::thread_local! {
+    pub(crate) static ACCESSES: core::cell::Cell<usize> = const { core::cell::Cell::new(0) };
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+pub struct UnionFind {
+    len: u16,
+    bits: u8,
+    packed: Vec<u8>,
+}
+
+impl UnionFind {
+    pub fn new(len: usize) -> Self {
+        let bits = (usize::BITS - len.saturating_sub(1).leading_zeros()).max(1) as u8;
+        let mut dsu = Self {
+            len: len as u16,
+            bits,
+            packed: vec![0; (len * bits as usize).div_ceil(8)],
+        };
+        for v in 0..len {
+            dsu.set_parent(v, v);
+        }
+        dsu
+    }
+
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn parent(&self, v: usize) -> usize {
+        #[cfg(all(test, feature = "std"))]
+        ACCESSES.with(|accesses| accesses.set(accesses.get() + 1));
+        let (byte, shift) = self.position(v);
+        ((self.window(byte) >> shift) & self.mask()) as usize
+    }
+
+    fn set_parent(&mut self, v: usize, parent: usize) {
+        #[cfg(all(test, feature = "std"))]
+        ACCESSES.with(|accesses| accesses.set(accesses.get() + 1));
+        let (byte, shift) = self.position(v);
+        let value = self.window(byte) & !(self.mask() << shift) | ((parent as u32) << shift);
+        for (i, packed) in self.packed.iter_mut().skip(byte).take(3).enumerate() {
+            *packed = (value >> (8 * i)) as u8;
+        }
+    }
+
+    fn position(&self, v: usize) -> (usize, usize) {
+        let offset = v * self.bits as usize;
+        (offset / 8, offset % 8)
+    }
+
+    fn mask(&self) -> u32 {
+        (1 << self.bits) - 1
+    }
+
+    // Up to 16 bits of a node starting anywhere in a byte fit in 3 bytes.
+    fn window(&self, byte: usize) -> u32 {
+        self.packed
+            .iter()
+            .skip(byte)
+            .take(3)
+            .enumerate()
+            .fold(0, |value, (i, packed)| value | (*packed as u32) << (8 * i))
+    }
+
+    pub fn find(&mut self, mut v: usize) -> usize {
+        while self.parent(v) != v {
+            let grandparent = self.parent(self.parent(v));
+            self.set_parent(v, grandparent);
+            v = grandparent;
+        }
+        v
+    }
+
+    pub fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            // Roots of bigger indices are border nodes, keep them as roots.
+            let (child, root) = if a < b { (a, b) } else { (b, a) };
+            self.set_parent(child, root);
+        }
+    }
+
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+}
+
+#[cfg(test)]
+mod union_find_tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::UnionFind;
+
+    fn parents(dsu: &UnionFind) -> Vec<usize> {
+        (0..dsu.len()).map(|v| dsu.parent(v)).collect()
+    }
+
+    #[test]
+    fn test_union_find() {
+        let mut dsu = UnionFind::new(8);
+        assert!(!dsu.connected(0, 1));
+
+        dsu.union(0, 1);
+        dsu.union(2, 3);
+        assert!(dsu.connected(0, 1));
+        assert!(dsu.connected(3, 2));
+        assert!(!dsu.connected(1, 2));
+
+        dsu.union(1, 3);
+        assert!(dsu.connected(0, 2));
+        assert!(!dsu.connected(0, 4));
+        assert_eq!(dsu.find(0), 3);
+
+        dsu.union(7, 0);
+        assert_eq!(dsu.find(2), 7);
+        assert!(dsu.connected(7, 1));
+    }
+
+    #[test]
+    fn test_path_compression() {
+        let mut dsu = UnionFind::new(6);
+        for i in 0..5 {
+            dsu.union(i, i + 1);
+        }
+        assert_eq!(parents(&dsu), vec![1, 2, 3, 4, 5, 5]);
+        assert_eq!(dsu.find(0), 5);
+        assert_eq!(parents(&dsu), vec![2, 2, 4, 4, 5, 5]);
+    }
+
+    #[test]
+    fn test_packed_parents() {
+        // The nodes of the largest board need 11 bits.
+        let len = 32 * 32 + 4;
+        let mut dsu = UnionFind::new(len);
+        assert_eq!(dsu.packed.len(), (len * 11).div_ceil(8));
+        assert_eq!(parents(&dsu), (0..len).collect::<Vec<usize>>());
+        for v in 0..len - 1 {
+            dsu.union(v, len - 1);
+        }
+        assert!((0..len).all(|v| dsu.parent(v) == len - 1));
+
+        let dsu = UnionFind::new(11 * 11 + 4);
+        assert_eq!(dsu.packed.len(), 110);
+        assert_eq!(UnionFind::new(1).parent(0), 0);
+    }
+}
@@ -0,0 +1,79 @@
+use hex_core::{evaluate, Evaluation};
+
+use crate::error::HexError;
+use crate::*;
+
+// The evaluation solves a system of equations over the cells, so its cost grows
+// as the cube of the number of cells.
+pub const MAX_EVALUATED_FIELD_SIZE: usize = 9;
+
+#[near_bindgen]
+impl Contract {
+    #[handle_result]
+    pub fn evaluate_position(&self, index: GameIndex) -> Result<Evaluation, HexError> {
+        let game_with_data = self.games.get(index).ok_or(HexError::GameNotFound)?;
+        game_with_data.check_position_visible()?;
+        let board = &game_with_data.game.state.board;
+        if board.width > MAX_EVALUATED_FIELD_SIZE || board.height > MAX_EVALUATED_FIELD_SIZE {
+            return Err(HexError::BoardTooBigToEvaluate);
+        }
+        Ok(evaluate(board))
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod analysis_tests {
+    use near_sdk::{
+        test_utils::{accounts, VMContextBuilder},
+        testing_env, AccountId, ONE_NEAR,
+    };
+
+    use hex_core::Cell;
+
+    use crate::{error::HexError, test_utils::create_accepted_game, Contract, MoveType};
+
+    fn get_context(account: AccountId) -> near_sdk::VMContext {
+        VMContextBuilder::new()
+            .predecessor_account_id(account)
+            .attached_deposit(10 * ONE_NEAR)
+            .build()
+    }
+
+    #[test]
+    fn test_evaluate_position() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        let id = create_accepted_game(&mut contract, accounts(0), accounts(1), Some(5), None, None);
+        assert_eq!(contract.evaluate_position(id).unwrap().leader(), None);
+
+        testing_env!(get_context(accounts(0)));
+        contract
+            .make_move(id, MoveType::PLACE, Some(Cell::new(2, 2)))
+            .unwrap();
+        let evaluation = contract.evaluate_position(id).unwrap();
+        assert_eq!(evaluation.leader(), Some(1));
+        assert_eq!(evaluation.second_player.heatmap[2][2], 0.0);
+    }
+
+    #[test]
+    fn test_evaluate_position_errors() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        let id = create_accepted_game(
+            &mut contract,
+            accounts(0),
+            accounts(1),
+            Some(10),
+            Some(9),
+            None,
+        );
+        assert_eq!(
+            contract.evaluate_position(id),
+            Err(HexError::BoardTooBigToEvaluate)
+        );
+        assert_eq!(
+            contract.evaluate_position(id + 1),
+            Err(HexError::GameNotFound)
+        );
+    }
+}
@@ -0,0 +1,400 @@
+use hex_core::MAX_FIELD_SIZE;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::error::HexError;
+use crate::game::Rules;
+use crate::wager::{refund_deposit_with_stake, release_storage, transfer_stake};
+use crate::*;
+
+pub type ChallengeIndex = u64;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Challenge {
+    pub challenger: AccountId,
+    pub opponent: Option<AccountId>,
+    pub field_size: usize,
+    pub field_height: usize,
+    pub rules: Rules,
+    pub stake: U128,
+    pub stake_token: Option<AccountId>,
+}
+
+#[near_bindgen]
+impl Contract {
+    #[payable]
+    #[handle_result]
+    pub fn post_challenge(
+        &mut self,
+        field_size: Option<usize>,
+        field_height: Option<usize>,
+        rules: Option<Rules>,
+        opponent: Option<AccountId>,
+        stake: Option<U128>,
+    ) -> Result<ChallengeIndex, HexError> {
+        let initial_storage_usage = env::storage_usage();
+
+        let stake = stake.map(|x| x.0).unwrap_or(0);
+        let field_size = field_size.unwrap_or(11);
+        let index = self.internal_post_challenge(Challenge {
+            challenger: env::predecessor_account_id(),
+            opponent,
+            field_size,
+            field_height: field_height.unwrap_or(field_size),
+            rules: rules.unwrap_or_default(),
+            stake: U128(stake),
+            stake_token: None,
+        })?;
+
+        let required_storage_in_bytes = env::storage_usage() - initial_storage_usage;
+        refund_deposit_with_stake(required_storage_in_bytes, stake)?;
+        Ok(index)
+    }
+
+    #[payable]
+    #[handle_result]
+    pub fn accept_challenge(&mut self, index: ChallengeIndex) -> Result<GameIndex, HexError> {
+        let initial_storage_usage = env::storage_usage();
+
+        let challenge = self
+            .challenges
+            .get(&index)
+            .ok_or(HexError::ChallengeNotFound)?;
+        if challenge.stake_token.is_some() {
+            return Err(HexError::StakeInTokens);
+        }
+        let game_index = self.internal_accept_challenge(index, env::predecessor_account_id())?;
+
+        let required_storage_in_bytes = env::storage_usage().saturating_sub(initial_storage_usage);
+        refund_deposit_with_stake(required_storage_in_bytes, challenge.stake.0)?;
+        Ok(game_index)
+    }
+
+    #[handle_result]
+    pub fn cancel_challenge(&mut self, index: ChallengeIndex) -> Result<Challenge, HexError> {
+        let initial_storage_usage = env::storage_usage();
+
+        let challenge = self
+            .challenges
+            .get(&index)
+            .ok_or(HexError::ChallengeNotFound)?;
+        if env::predecessor_account_id() != challenge.challenger {
+            return Err(HexError::NotChallenger);
+        }
+        self.challenges.remove(&index);
+        HexGameEvent::ChallengeCancelled {
+            challenge_id: index,
+        }
+        .emit();
+        let storage_refund = release_storage(
+            self,
+            &challenge.challenger,
+            challenge.stake_token.is_some(),
+            initial_storage_usage,
+        );
+        if challenge.stake.0 + storage_refund > 0 {
+            transfer_stake(
+                challenge.challenger.clone(),
+                challenge.stake_token.clone(),
+                challenge.stake.0 + storage_refund,
+            );
+        }
+        Ok(challenge)
+    }
+
+    pub fn get_challenge(&self, index: ChallengeIndex) -> Option<Challenge> {
+        self.challenges.get(&index)
+    }
+
+    pub fn list_open_challenges(
+        &self,
+        from: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<(ChallengeIndex, Challenge)> {
+        let keys = self.challenges.keys_as_vector();
+        let values = self.challenges.values_as_vector();
+        let from = from.unwrap_or(0);
+        let to = std::cmp::min(from.saturating_add(limit.unwrap_or(50)), keys.len());
+        (from..to)
+            .map(|i| (keys.get(i).unwrap(), values.get(i).unwrap()))
+            .collect()
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_post_challenge(
+        &mut self,
+        challenge: Challenge,
+    ) -> Result<ChallengeIndex, HexError> {
+        if challenge.field_size > MAX_FIELD_SIZE || challenge.field_height > MAX_FIELD_SIZE {
+            return Err(HexError::FieldTooBig);
+        }
+        if challenge.opponent.as_ref() == Some(&challenge.challenger) {
+            return Err(HexError::SelfChallenge);
+        }
+        // The game is created when the challenge is accepted, the rules are
+        // checked on a board of the same size now.
+        Game::new(
+            challenge.challenger.clone(),
+            challenge.challenger.clone(),
+            challenge.field_size,
+            challenge.field_height,
+        )?
+        .set_rules(challenge.rules.clone())?;
+        let index = self.next_challenge_index;
+        self.next_challenge_index += 1;
+        self.challenges.insert(&index, &challenge);
+        HexGameEvent::ChallengePosted {
+            challenge_id: index,
+            challenger: &challenge.challenger,
+            opponent: challenge.opponent.as_ref(),
+            width: challenge.field_size,
+            height: challenge.field_height,
+            stake: challenge.stake,
+            stake_token: challenge.stake_token.as_ref(),
+        }
+        .emit();
+        Ok(index)
+    }
+
+    pub(crate) fn internal_accept_challenge(
+        &mut self,
+        index: ChallengeIndex,
+        account: AccountId,
+    ) -> Result<GameIndex, HexError> {
+        let challenge = self
+            .challenges
+            .remove(&index)
+            .ok_or(HexError::ChallengeNotFound)?;
+        if account == challenge.challenger {
+            return Err(HexError::SelfChallenge);
+        }
+        if matches!(&challenge.opponent, Some(opponent) if *opponent != account) {
+            return Err(HexError::ChallengeForAnotherAccount);
+        }
+
+        let mut game_with_data = GameWithData::new(
+            self.games.len(),
+            challenge.challenger,
+            account.clone(),
+            challenge.field_size,
+            challenge.field_height,
+        )?;
+        game_with_data.game.set_rules(challenge.rules)?;
+        game_with_data.game.stake = challenge.stake;
+        game_with_data.game.stake_token = challenge.stake_token;
+        let game_index = self.internal_add_game(&game_with_data);
+        HexGameEvent::ChallengeAccepted {
+            challenge_id: index,
+            account_id: &account,
+            game_id: game_index,
+        }
+        .emit();
+        Ok(game_index)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod challenge_tests {
+    use near_sdk::{
+        env,
+        json_types::U128,
+        mock::VmAction,
+        test_utils::{accounts, get_created_receipts, VMContextBuilder},
+        testing_env, AccountId, Balance, ONE_NEAR,
+    };
+
+    use hex_core::Cell;
+
+    use crate::{
+        error::HexError,
+        game::{Opening, Rules},
+        Contract, MoveType,
+    };
+
+    fn get_context(account: AccountId) -> near_sdk::VMContext {
+        VMContextBuilder::new()
+            .predecessor_account_id(account)
+            .attached_deposit(10 * ONE_NEAR)
+            .build()
+    }
+
+    #[test]
+    fn test_post_and_accept_challenge() {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        let rules = Rules {
+            timeout: Some(10),
+            ..Rules::default()
+        };
+        let id = contract
+            .post_challenge(Some(5), None, Some(rules.clone()), None, None)
+            .unwrap();
+        assert_eq!(id, 0);
+        assert!(contract.get_game(0).is_none());
+
+        testing_env!(get_context(accounts(1)));
+        let game_id = contract.accept_challenge(id).unwrap();
+        assert!(contract.get_challenge(id).is_none());
+
+        let game = contract.get_game(game_id).unwrap();
+        assert_eq!(game.first_player, accounts(0));
+        assert_eq!(game.second_player, accounts(1));
+        assert_eq!(game.state.board.width, 5);
+        assert_eq!(game.state.board.height, 5);
+        assert_eq!(game.rules, rules);
+        assert_eq!(game.pending_acceptance, None);
+
+        testing_env!(get_context(accounts(0)));
+        contract
+            .make_move(game_id, MoveType::PLACE, Some(Cell::new(2, 2)))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_rectangular_challenge() {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        let id = contract
+            .post_challenge(Some(7), Some(5), None, None, None)
+            .unwrap();
+        assert_eq!(contract.get_challenge(id).unwrap().field_height, 5);
+
+        testing_env!(get_context(accounts(1)));
+        let game_id = contract.accept_challenge(id).unwrap();
+        let game = contract.get_game(game_id).unwrap();
+        assert_eq!((game.state.board.width, game.state.board.height), (7, 5));
+    }
+
+    #[test]
+    fn test_accept_challenge_wrong_opponent() {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        let id = contract
+            .post_challenge(None, None, None, Some(accounts(1)), None)
+            .unwrap();
+
+        testing_env!(get_context(accounts(2)));
+        assert_eq!(
+            contract.accept_challenge(id),
+            Err(HexError::ChallengeForAnotherAccount)
+        );
+    }
+
+    #[test]
+    fn test_challenge_with_incorrect_rules() {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        let rules = Rules {
+            opening: Opening::Balanced(vec![Cell::new(5, 5)]),
+            ..Rules::default()
+        };
+        assert_eq!(
+            contract.post_challenge(Some(5), None, Some(rules), None, None),
+            Err(HexError::CellOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_accept_own_challenge() {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        let id = contract
+            .post_challenge(None, None, None, None, None)
+            .unwrap();
+        assert_eq!(contract.accept_challenge(id), Err(HexError::SelfChallenge));
+    }
+
+    #[test]
+    fn test_challenge_with_stake() {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        let id = contract
+            .post_challenge(Some(3), None, None, None, Some(U128(ONE_NEAR)))
+            .unwrap();
+
+        testing_env!(get_context(accounts(1)));
+        let game_id = contract.accept_challenge(id).unwrap();
+        let game = contract.get_game(game_id).unwrap();
+        assert_eq!(game.stake, U128(ONE_NEAR));
+
+        testing_env!(get_context(accounts(0)));
+        contract.make_move(game_id, MoveType::RESIGN, None).unwrap();
+        let receipts = get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, accounts(1));
+        assert_eq!(
+            receipts[0].actions,
+            vec![VmAction::Transfer {
+                deposit: 2 * ONE_NEAR
+            }]
+        );
+    }
+
+    #[test]
+    fn test_cancel_challenge() {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        let id = contract
+            .post_challenge(None, None, None, None, Some(U128(ONE_NEAR)))
+            .unwrap();
+
+        testing_env!(get_context(accounts(0)));
+        let storage_usage = env::storage_usage();
+        contract.cancel_challenge(id).unwrap();
+        assert!(contract.get_challenge(id).is_none());
+
+        // The stake comes back with the storage the challenge took.
+        let storage_refund =
+            env::storage_byte_cost() * Balance::from(storage_usage - env::storage_usage());
+        assert!(storage_refund > 0);
+        let receipts = get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, accounts(0));
+        assert_eq!(
+            receipts[0].actions,
+            vec![VmAction::Transfer {
+                deposit: ONE_NEAR + storage_refund
+            }]
+        );
+    }
+
+    #[test]
+    fn test_cancel_challenge_not_challenger() {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        let id = contract
+            .post_challenge(None, None, None, None, None)
+            .unwrap();
+
+        testing_env!(get_context(accounts(1)));
+        assert_eq!(
+            contract.cancel_challenge(id).err(),
+            Some(HexError::NotChallenger)
+        );
+    }
+
+    #[test]
+    fn test_list_open_challenges() {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        for size in 3..8 {
+            contract
+                .post_challenge(Some(size), None, None, None, None)
+                .unwrap();
+        }
+        testing_env!(get_context(accounts(1)));
+        contract.accept_challenge(1).unwrap();
+
+        let challenges = contract.list_open_challenges(None, None);
+        assert_eq!(challenges.len(), 4);
+        assert!(challenges.iter().all(|(id, _)| *id != 1));
+
+        let page = contract.list_open_challenges(Some(1), Some(2));
+        assert_eq!(page.len(), 2);
+        assert_eq!(page[0].0, challenges[1].0);
+        assert_eq!(page[1].0, challenges[2].0);
+        assert!(contract.list_open_challenges(Some(10), None).is_empty());
+    }
+}
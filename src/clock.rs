@@ -0,0 +1,345 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen};
+
+use crate::error::HexError;
+use crate::*;
+
+const NANOSECONDS_IN_MILLISECOND: u64 = 1_000_000;
+// 30 days, the limit of every time of a time control.
+pub const MAX_CLOCK_TIME: u64 = 30 * 24 * 60 * 60 * 1_000;
+
+// Chess clock of both players, all the times are in milliseconds.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub enum TimeControl {
+    // `increment` is added to the clock after every move.
+    Fischer {
+        main_time: u64,
+        increment: u64,
+    },
+    // After the main time every move must be made within `period_time`, each
+    // longer move uses up a period.
+    ByoYomi {
+        main_time: u64,
+        periods: u32,
+        period_time: u64,
+    },
+}
+
+impl TimeControl {
+    pub fn validate(&self) -> Result<(), HexError> {
+        let valid = match *self {
+            TimeControl::Fischer {
+                main_time,
+                increment,
+            } => main_time > 0 && main_time <= MAX_CLOCK_TIME && increment <= MAX_CLOCK_TIME,
+            TimeControl::ByoYomi {
+                main_time,
+                periods,
+                period_time,
+            } => {
+                main_time <= MAX_CLOCK_TIME
+                    && periods > 0
+                    && period_time > 0
+                    && period_time <= MAX_CLOCK_TIME
+            }
+        };
+        if !valid {
+            return Err(HexError::IncorrectTimeControl);
+        }
+        Ok(())
+    }
+}
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, Default, PartialEq, Clone, Copy,
+)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct Clock {
+    pub main_time: u64,
+    // Byo-yomi periods left, always 0 with the Fischer clock.
+    pub periods: u32,
+}
+
+impl Clock {
+    pub fn new(control: &TimeControl) -> Self {
+        match *control {
+            TimeControl::Fischer { main_time, .. } => Clock {
+                main_time,
+                periods: 0,
+            },
+            TimeControl::ByoYomi {
+                main_time, periods, ..
+            } => Clock { main_time, periods },
+        }
+    }
+
+    // The clock after thinking for `elapsed` milliseconds, `None` when the
+    // flag has fallen.
+    pub fn spend(&self, control: &TimeControl, elapsed: u64) -> Option<Clock> {
+        if elapsed <= self.main_time {
+            return Some(Clock {
+                main_time: self.main_time - elapsed,
+                periods: self.periods,
+            });
+        }
+        match *control {
+            TimeControl::Fischer { .. } => None,
+            TimeControl::ByoYomi { period_time, .. } => {
+                // A move within the period keeps it.
+                let used = (elapsed - self.main_time - 1) / period_time;
+                if used >= self.periods as u64 {
+                    return None;
+                }
+                Some(Clock {
+                    main_time: 0,
+                    periods: self.periods - used as u32,
+                })
+            }
+        }
+    }
+
+    pub fn add_increment(self, control: &TimeControl) -> Clock {
+        match *control {
+            TimeControl::Fischer { increment, .. } => Clock {
+                // The clock can't grow past the limit with many quick moves.
+                main_time: self.main_time.saturating_add(increment),
+                ..self
+            },
+            TimeControl::ByoYomi { .. } => self,
+        }
+    }
+}
+
+pub fn elapsed_since(timestamp: u64) -> u64 {
+    env::block_timestamp().saturating_sub(timestamp) / NANOSECONDS_IN_MILLISECOND
+}
+
+#[near_bindgen]
+impl Contract {
+    // Clocks of the first and the second player as of the current block.
+    #[handle_result]
+    pub fn get_clocks(&self, index: GameIndex) -> Result<Option<[Clock; 2]>, HexError> {
+        let game = self.games.get(index).ok_or(HexError::GameNotFound)?.game;
+        Ok(game.current_clocks())
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod clock_tests {
+    use near_sdk::{
+        test_utils::{accounts, VMContextBuilder},
+        testing_env, AccountId, ONE_NEAR,
+    };
+
+    use hex_core::Cell;
+
+    use super::{Clock, TimeControl, MAX_CLOCK_TIME, NANOSECONDS_IN_MILLISECOND};
+    use crate::{
+        error::HexError,
+        game::{FinishReason, GameIndex, Rules},
+        test_utils::create_accepted_game,
+        Contract, MoveType,
+    };
+
+    const FISCHER: TimeControl = TimeControl::Fischer {
+        main_time: 60_000,
+        increment: 5_000,
+    };
+    const BYO_YOMI: TimeControl = TimeControl::ByoYomi {
+        main_time: 10_000,
+        periods: 3,
+        period_time: 30_000,
+    };
+
+    fn clock(main_time: u64, periods: u32) -> Clock {
+        Clock { main_time, periods }
+    }
+
+    fn get_context(account: AccountId, millisecond: u64) -> near_sdk::VMContext {
+        VMContextBuilder::new()
+            .predecessor_account_id(account)
+            .attached_deposit(10 * ONE_NEAR)
+            .block_timestamp(millisecond * NANOSECONDS_IN_MILLISECOND)
+            .build()
+    }
+
+    fn create_game(contract: &mut Contract, control: TimeControl) -> GameIndex {
+        let rules = Rules {
+            clock: Some(control),
+            ..Rules::default()
+        };
+        create_accepted_game(
+            contract,
+            accounts(0),
+            accounts(1),
+            Some(5),
+            None,
+            Some(rules),
+        )
+    }
+
+    #[test]
+    fn test_fischer_clock() {
+        let start = Clock::new(&FISCHER);
+        assert_eq!(start, clock(60_000, 0));
+        assert_eq!(start.spend(&FISCHER, 60_000), Some(clock(0, 0)));
+        assert_eq!(start.spend(&FISCHER, 60_001), None);
+        assert_eq!(
+            start
+                .spend(&FISCHER, 15_000)
+                .unwrap()
+                .add_increment(&FISCHER),
+            clock(50_000, 0)
+        );
+        assert_eq!(
+            clock(u64::MAX, 0).add_increment(&FISCHER),
+            clock(u64::MAX, 0)
+        );
+    }
+
+    #[test]
+    fn test_byo_yomi_clock() {
+        let start = Clock::new(&BYO_YOMI);
+        assert_eq!(start, clock(10_000, 3));
+        assert_eq!(start.spend(&BYO_YOMI, 4_000), Some(clock(6_000, 3)));
+        // Moves within a period keep it.
+        assert_eq!(start.spend(&BYO_YOMI, 40_000), Some(clock(0, 3)));
+        assert_eq!(start.spend(&BYO_YOMI, 40_001), Some(clock(0, 2)));
+        assert_eq!(start.spend(&BYO_YOMI, 100_000), Some(clock(0, 1)));
+        assert_eq!(start.spend(&BYO_YOMI, 100_001), None);
+        assert_eq!(
+            clock(0, 1)
+                .spend(&BYO_YOMI, 1_000)
+                .unwrap()
+                .add_increment(&BYO_YOMI),
+            clock(0, 1)
+        );
+    }
+
+    #[test]
+    fn test_clocks_in_game() {
+        testing_env!(get_context(accounts(2), 1_000));
+        let mut contract = Contract::new(None);
+        let id = create_game(&mut contract, FISCHER);
+        let game = contract.get_game(id).unwrap();
+        assert_eq!(game.clocks, Some([clock(60_000, 0); 2]));
+
+        testing_env!(get_context(accounts(0), 21_000));
+        assert_eq!(
+            contract.get_clocks(id).unwrap(),
+            Some([clock(40_000, 0), clock(60_000, 0)])
+        );
+        contract
+            .make_move(id, MoveType::PLACE, Some(Cell::new(2, 2)))
+            .unwrap();
+        assert_eq!(
+            contract.get_clocks(id).unwrap(),
+            Some([clock(45_000, 0), clock(60_000, 0)])
+        );
+
+        testing_env!(get_context(accounts(1), 31_000));
+        contract.make_move(id, MoveType::SWAP, None).unwrap();
+        testing_env!(get_context(accounts(0), 41_000));
+        assert_eq!(
+            contract.get_clocks(id).unwrap(),
+            Some([clock(35_000, 0), clock(55_000, 0)])
+        );
+
+        let game =
+            create_accepted_game(&mut contract, accounts(0), accounts(1), Some(5), None, None);
+        assert_eq!(contract.get_clocks(game).unwrap(), None);
+        assert_eq!(contract.get_clocks(game + 1), Err(HexError::GameNotFound));
+    }
+
+    #[test]
+    fn test_flag_fall() {
+        testing_env!(get_context(accounts(2), 0));
+        let mut contract = Contract::new(None);
+        let id = create_game(&mut contract, BYO_YOMI);
+
+        testing_env!(get_context(accounts(0), 5_000));
+        contract
+            .make_move(id, MoveType::PLACE, Some(Cell::new(2, 2)))
+            .unwrap();
+
+        // The second player is still within the last period.
+        testing_env!(get_context(accounts(0), 105_000));
+        assert_eq!(contract.claim_timeout(id), Err(HexError::NotTimedOut));
+        assert_eq!(
+            contract.get_clocks(id).unwrap(),
+            Some([clock(5_000, 3), clock(0, 1)])
+        );
+
+        testing_env!(get_context(accounts(1), 105_001));
+        assert_eq!(
+            contract.make_move(id, MoveType::PLACE, Some(Cell::new(1, 1))),
+            Err(HexError::TimeIsUp)
+        );
+        assert_eq!(
+            contract.claim_timeout(id),
+            Err(HexError::IncorrectPredecessor)
+        );
+        assert_eq!(
+            contract.get_clocks(id).unwrap(),
+            Some([clock(5_000, 3), clock(0, 0)])
+        );
+
+        testing_env!(get_context(accounts(0), 105_001));
+        let game = contract.claim_timeout(id).unwrap();
+        assert_eq!(game.winner, Some(accounts(0)));
+        assert_eq!(game.finish_reason, Some(FinishReason::Timeout));
+    }
+
+    #[test]
+    fn test_incorrect_time_control() {
+        testing_env!(get_context(accounts(2), 0));
+        let mut contract = Contract::new(None);
+        for control in [
+            TimeControl::Fischer {
+                main_time: 0,
+                increment: 1_000,
+            },
+            TimeControl::ByoYomi {
+                main_time: 0,
+                periods: 0,
+                period_time: 10_000,
+            },
+            TimeControl::ByoYomi {
+                main_time: 0,
+                periods: 5,
+                period_time: 0,
+            },
+            TimeControl::Fischer {
+                main_time: MAX_CLOCK_TIME + 1,
+                increment: 0,
+            },
+            TimeControl::Fischer {
+                main_time: 60_000,
+                increment: u64::MAX,
+            },
+            TimeControl::ByoYomi {
+                main_time: u64::MAX,
+                periods: 1,
+                period_time: 10_000,
+            },
+            TimeControl::ByoYomi {
+                main_time: 0,
+                periods: 1,
+                period_time: MAX_CLOCK_TIME + 1,
+            },
+        ] {
+            let rules = Rules {
+                clock: Some(control),
+                ..Rules::default()
+            };
+            assert_eq!(
+                contract.create_game(accounts(0), accounts(1), None, None, Some(rules), None),
+                Err(HexError::IncorrectTimeControl)
+            );
+        }
+    }
+}
@@ -0,0 +1,429 @@
+use hex_core::{Board, Cell};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen};
+
+use crate::error::HexError;
+use crate::events::HexGameEvent;
+use crate::*;
+
+// Dark Hex part of a game. Only the hashes of the moves are stored until both
+// players reveal them, the board of the game has no hidden counters.
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct DarkState {
+    // Hashes of the hidden moves of both players in the order of the plies.
+    pub commitments: Vec<Vec<u8>>,
+    // Cells of the hidden moves of the players who have revealed them.
+    pub revealed: Vec<(u8, Vec<Cell>)>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+pub struct RevealedMove {
+    pub cell: Cell,
+    pub salt: String,
+}
+
+// SHA-256 of the notation of the cell and a secret salt separated with a
+// colon, e.g. of "c3:4f1d09".
+pub fn move_hash(cell: &Cell, salt: &str) -> Vec<u8> {
+    env::sha256(format!("{}:{}", cell.to_notation(), salt).as_bytes())
+}
+
+pub fn parse_hash(hash: &str) -> Result<Vec<u8>, HexError> {
+    if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(HexError::IncorrectHash);
+    }
+    Ok((0..hash.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hash[i..i + 2], 16).unwrap())
+        .collect())
+}
+
+#[near_bindgen]
+impl Contract {
+    #[handle_result]
+    pub fn commit_move(&mut self, index: GameIndex, hash: String) -> Result<Game, HexError> {
+        let mut game_with_data = self.internal_get_active_game(index)?;
+        game_with_data.commit_move(parse_hash(&hash)?)?;
+        HexGameEvent::HiddenMoveMade {
+            game_id: index,
+            player: &env::predecessor_account_id(),
+            ply: game_with_data.moves.len() + game_with_data.dark.commitments.len() as u64 - 1,
+        }
+        .emit();
+
+        self.games.replace(index, &game_with_data);
+        Ok(game_with_data.game)
+    }
+
+    #[handle_result]
+    pub fn reveal_moves(
+        &mut self,
+        index: GameIndex,
+        moves: Vec<RevealedMove>,
+    ) -> Result<Game, HexError> {
+        let mut game_with_data = self.internal_get_active_game(index)?;
+        game_with_data.reveal_moves(&moves)?;
+        HexGameEvent::MovesRevealed {
+            game_id: index,
+            player: &env::predecessor_account_id(),
+        }
+        .emit();
+
+        if game_with_data.game.is_finished {
+            self.internal_on_game_finished(index, &game_with_data.game);
+        }
+
+        self.games.replace(index, &game_with_data);
+        Ok(game_with_data.game)
+    }
+
+    // Nothing of a view call is stored on chain, but the RPC node sees the
+    // salts, so clients may rather draw the counters themselves.
+    #[handle_result]
+    pub fn get_board_view(
+        &self,
+        index: GameIndex,
+        player: u8,
+        moves: Vec<RevealedMove>,
+    ) -> Result<Board, HexError> {
+        let game_with_data = self.games.get(index).ok_or(HexError::GameNotFound)?;
+        game_with_data.board_view(player, &moves)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod dark_tests {
+    use near_sdk::{
+        test_utils::{accounts, VMContextBuilder},
+        testing_env, AccountId, ONE_NEAR,
+    };
+
+    use hex_core::{Board, Cell};
+
+    use super::{move_hash, parse_hash, RevealedMove};
+    use crate::{
+        error::HexError,
+        game::{FinishReason, GameIndex, Rules},
+        test_utils::create_accepted_game,
+        Contract, MoveType,
+    };
+
+    fn get_context(account: AccountId) -> near_sdk::VMContext {
+        VMContextBuilder::new()
+            .predecessor_account_id(account)
+            .attached_deposit(10 * ONE_NEAR)
+            .build()
+    }
+
+    fn hash(notation: &str, salt: &str) -> String {
+        move_hash(&Cell::from_notation(notation).unwrap(), salt)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    fn revealed(notations: &[&str]) -> Vec<RevealedMove> {
+        notations
+            .iter()
+            .map(|notation| RevealedMove {
+                cell: Cell::from_notation(notation).unwrap(),
+                salt: "salt".to_string(),
+            })
+            .collect()
+    }
+
+    fn create_dark_game(contract: &mut Contract, size: usize) -> GameIndex {
+        let rules = Rules {
+            dark: true,
+            timeout: Some(100),
+            ..Rules::default()
+        };
+        create_accepted_game(
+            contract,
+            accounts(0),
+            accounts(1),
+            Some(size),
+            None,
+            Some(rules),
+        )
+    }
+
+    fn commit(contract: &mut Contract, id: GameIndex, account: AccountId, notation: &str) {
+        testing_env!(get_context(account));
+        contract.commit_move(id, hash(notation, "salt")).unwrap();
+    }
+
+    #[test]
+    fn test_parse_hash() {
+        let hash = parse_hash(&"0f".repeat(32)).unwrap();
+        assert_eq!(hash, vec![15; 32]);
+        assert_eq!(parse_hash("0f"), Err(HexError::IncorrectHash));
+        assert_eq!(parse_hash(&"+f".repeat(32)), Err(HexError::IncorrectHash));
+    }
+
+    #[test]
+    fn test_dark_game() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        let id = create_dark_game(&mut contract, 3);
+
+        // The second player runs into the hidden counter at b2.
+        commit(&mut contract, id, accounts(0), "b2");
+        commit(&mut contract, id, accounts(1), "b2");
+        commit(&mut contract, id, accounts(0), "b1");
+        commit(&mut contract, id, accounts(1), "a1");
+        commit(&mut contract, id, accounts(0), "b3");
+        // Only the hashes are stored, not the counters.
+        let game_with_data = contract.games.get(id).unwrap();
+        assert_eq!(game_with_data.dark.commitments.len(), 5);
+        assert_eq!(game_with_data.game.state.board, Board::new(3, 3).unwrap());
+        assert_eq!(game_with_data.moves.len(), 0);
+        let game = contract.get_game(id).unwrap();
+        assert_eq!(game.state.turn, 5);
+        assert_eq!(game.get_current_player(), accounts(1));
+        assert!(matches!(
+            contract.get_moves(id, None, None),
+            Err(HexError::PositionHidden)
+        ));
+        assert_eq!(contract.export_sgf(id), Err(HexError::PositionHidden));
+
+        // Each player proves their own counters to see them on the board.
+        let mut red_view = Board::new(3, 3).unwrap();
+        for cell in [Cell::new(1, 1), Cell::new(1, 0), Cell::new(1, 2)] {
+            red_view.set_cell(&cell, 1).unwrap();
+        }
+        let red_moves = revealed(&["b2", "b1", "b3"]);
+        assert_eq!(contract.get_board_view(id, 1, red_moves).unwrap(), red_view);
+        let mut blue_view = Board::new(3, 3).unwrap();
+        blue_view.set_cell(&Cell::new(1, 1), 2).unwrap();
+        blue_view.set_cell(&Cell::new(0, 0), 2).unwrap();
+        let blue_moves = revealed(&["b2", "a1"]);
+        assert_eq!(
+            contract.get_board_view(id, 2, blue_moves).unwrap(),
+            blue_view
+        );
+        assert_eq!(
+            contract.get_board_view(id, 2, revealed(&["a1", "b2"])),
+            Err(HexError::CommitmentMismatch)
+        );
+        assert_eq!(
+            contract.get_board_view(id, 3, vec![]),
+            Err(HexError::IncorrectPlayerNumber)
+        );
+
+        // The first player believes they've connected and reveals first.
+        testing_env!(get_context(accounts(0)));
+        let game = contract
+            .reveal_moves(id, revealed(&["b2", "b1", "b3"]))
+            .unwrap();
+        assert!(!game.is_finished);
+        assert_eq!(game.state.board, Board::new(3, 3).unwrap());
+        assert_eq!(contract.export_sgf(id), Err(HexError::PositionHidden));
+        testing_env!(get_context(accounts(1)));
+        assert_eq!(
+            contract.commit_move(id, hash("c1", "salt")),
+            Err(HexError::RevealStarted)
+        );
+        let game = contract.reveal_moves(id, revealed(&["b2", "a1"])).unwrap();
+        assert!(game.is_finished);
+        assert_eq!(game.winner, Some(accounts(0)));
+        assert_eq!(game.finish_reason, Some(FinishReason::Connection));
+
+        let board = game.state.board;
+        assert_eq!(board.get_cell(&Cell::new(0, 0)), Ok(2));
+        assert_eq!(board.get_cell(&Cell::new(1, 1)), Ok(1));
+        assert_eq!(board.get_cell(&Cell::new(1, 2)), Ok(1));
+        let moves = contract.get_moves(id, None, None).unwrap();
+        assert_eq!(moves.len(), 5);
+        assert_eq!(moves[1].move_type, MoveType::COLLISION);
+        assert_eq!(moves[1].cell, Some(Cell::new(1, 1)));
+        assert_eq!(moves[1].player, 2);
+        assert_eq!(moves[3].player, 2);
+        assert_eq!(contract.get_position_at(id, 5).unwrap(), board);
+        assert_eq!(contract.get_board_view(id, 2, vec![]).unwrap(), board);
+        let sgf = contract.export_sgf(id).unwrap();
+        assert_eq!(
+            sgf,
+            "(;FF[4]GM[11]SZ[3]PB[alice]PW[bob]RE[B+];B[b2];W[];B[b1];W[a1];B[b3])"
+        );
+        // Records of Dark Hex games can't be imported, a position has no
+        // passes.
+        assert_eq!(
+            contract.create_game_from_position(accounts(0), accounts(1), sgf, None, None, None),
+            Err(HexError::InvalidRecord(
+                "Position can't contain passes.".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_dark_game_continues_after_reveal() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        let id = create_dark_game(&mut contract, 3);
+
+        commit(&mut contract, id, accounts(0), "a1");
+        commit(&mut contract, id, accounts(1), "c3");
+        testing_env!(get_context(accounts(1)));
+        contract.reveal_moves(id, revealed(&["c3"])).unwrap();
+        testing_env!(get_context(accounts(0)));
+        let game = contract.reveal_moves(id, revealed(&["a1"])).unwrap();
+        // Nobody has connected, the game goes on in the open.
+        assert!(!game.is_finished);
+        assert_eq!(game.state.turn, 2);
+        assert_eq!(contract.get_moves(id, None, None).unwrap().len(), 2);
+        assert_eq!(
+            contract.commit_move(id, hash("b2", "salt")),
+            Err(HexError::RevealStarted)
+        );
+        contract
+            .make_move(id, MoveType::PLACE, Some(Cell::new(1, 1)))
+            .unwrap();
+        assert_eq!(
+            contract.reveal_moves(id, revealed(&["a1"])),
+            Err(HexError::RevealNotAllowed)
+        );
+    }
+
+    #[test]
+    fn test_dark_reveal_timeout() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        let rules = Rules {
+            dark: true,
+            timeout: Some(10),
+            ..Rules::default()
+        };
+        let id = create_accepted_game(
+            &mut contract,
+            accounts(0),
+            accounts(1),
+            Some(3),
+            None,
+            Some(rules),
+        );
+        commit(&mut contract, id, accounts(0), "b2");
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .block_index(11)
+            .build());
+        assert_eq!(contract.claim_timeout(id), Err(HexError::RevealRequired));
+        contract.reveal_moves(id, revealed(&["b2"])).unwrap();
+
+        // The opponent who doesn't reveal their moves loses on time.
+        let game = contract.claim_timeout(id).unwrap();
+        assert_eq!(game.winner, Some(accounts(0)));
+        assert_eq!(game.finish_reason, Some(FinishReason::Timeout));
+        // Their moves are never revealed.
+        assert!(matches!(
+            contract.get_moves(id, None, None),
+            Err(HexError::PositionHidden)
+        ));
+        assert_eq!(contract.export_sgf(id), Err(HexError::PositionHidden));
+    }
+
+    #[test]
+    fn test_dark_resign_after_reveal() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        let id = create_dark_game(&mut contract, 3);
+
+        commit(&mut contract, id, accounts(0), "b2");
+        for account in [accounts(0), accounts(1)] {
+            testing_env!(get_context(account));
+            assert_eq!(
+                contract.make_move(id, MoveType::RESIGN, None),
+                Err(HexError::RevealRequired)
+            );
+        }
+        testing_env!(get_context(accounts(0)));
+        contract.reveal_moves(id, revealed(&["b2"])).unwrap();
+        testing_env!(get_context(accounts(1)));
+        assert_eq!(
+            contract.make_move(id, MoveType::RESIGN, None),
+            Err(HexError::RevealRequired)
+        );
+        contract.reveal_moves(id, vec![]).unwrap();
+
+        let game = contract.make_move(id, MoveType::RESIGN, None).unwrap();
+        assert_eq!(game.winner, Some(accounts(0)));
+        assert_eq!(game.finish_reason, Some(FinishReason::Resignation));
+        let moves = contract.get_moves(id, None, None).unwrap();
+        assert_eq!(moves.len(), 2);
+        assert_eq!(moves[0].cell, Some(Cell::new(1, 1)));
+        assert_eq!(moves[1].move_type, MoveType::RESIGN);
+    }
+
+    #[test]
+    fn test_dark_move_errors() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        let id = create_dark_game(&mut contract, 3);
+        let b2 = Cell::new(1, 1);
+
+        testing_env!(get_context(accounts(0)));
+        assert_eq!(
+            contract.make_move(id, MoveType::PLACE, Some(b2)),
+            Err(HexError::MoveNotCommitted)
+        );
+        assert_eq!(
+            contract.commit_move(id, "b2".to_string()),
+            Err(HexError::IncorrectHash)
+        );
+        assert_eq!(
+            contract.reveal_moves(id, vec![]),
+            Err(HexError::RevealNotAllowed)
+        );
+        // Cells off the board can be committed, but not revealed.
+        contract.commit_move(id, hash("d1", "salt")).unwrap();
+        assert_eq!(
+            contract.commit_move(id, hash("a1", "salt")),
+            Err(HexError::IncorrectPredecessor)
+        );
+
+        testing_env!(get_context(accounts(1)));
+        assert_eq!(
+            contract.make_move(id, MoveType::SWAP, None),
+            Err(HexError::SwapNotAllowed)
+        );
+        // The player to move can't start the reveal.
+        assert_eq!(
+            contract.reveal_moves(id, vec![]),
+            Err(HexError::RevealNotAllowed)
+        );
+        testing_env!(get_context(accounts(2)));
+        assert_eq!(
+            contract.reveal_moves(id, vec![]),
+            Err(HexError::IncorrectPredecessor)
+        );
+        testing_env!(get_context(accounts(0)));
+        assert_eq!(
+            contract.reveal_moves(id, vec![]),
+            Err(HexError::CommitmentMismatch)
+        );
+        let mut moves = revealed(&["d1"]);
+        moves[0].salt = "pepper".to_string();
+        assert_eq!(
+            contract.reveal_moves(id, moves),
+            Err(HexError::CommitmentMismatch)
+        );
+        assert_eq!(
+            contract.reveal_moves(id, revealed(&["d1"])),
+            Err(HexError::CellOutOfBounds)
+        );
+
+        let id = create_accepted_game(&mut contract, accounts(0), accounts(1), Some(3), None, None);
+        testing_env!(get_context(accounts(0)));
+        assert_eq!(
+            contract.commit_move(id, hash("b2", "salt")),
+            Err(HexError::NotDarkGame)
+        );
+        assert_eq!(
+            contract.reveal_moves(id, vec![]),
+            Err(HexError::NotDarkGame)
+        );
+    }
+}
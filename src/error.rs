@@ -0,0 +1,300 @@
+use std::fmt;
+
+use hex_core::MAX_FIELD_SIZE;
+use near_sdk::{Balance, FunctionError};
+
+use crate::analysis::MAX_EVALUATED_FIELD_SIZE;
+use crate::clock::MAX_CLOCK_TIME;
+
+// Codes are part of the public API, never reuse or renumber them.
+#[derive(PartialEq, Clone, FunctionError)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+pub enum HexError {
+    FieldTooBig,
+    CellOutOfBounds,
+    CellOccupied,
+    IncorrectCellValue,
+    BoardTooBigToEvaluate,
+    EmptyField,
+
+    GameNotFound,
+    GameFinished,
+    GameNotAccepted,
+    IncorrectPredecessor,
+    NotYourTurn,
+    IncorrectPlayerNumber,
+    IncorrectMoveArgs,
+    SwapNotAllowed,
+    SwapOnRectangularBoard,
+    NoTimeLimit,
+    NotTimedOut,
+    NotEnoughMoves,
+    OpeningNotAllowed,
+    NoOpeningCells,
+    IncorrectTimeControl,
+    TimeIsUp,
+
+    IncorrectNotation,
+    InvalidRecord(String),
+    PositionAlreadyWon,
+    MovesOutOfOrder,
+    SizeMismatch,
+
+    SamePlayersForStake,
+    NotAPlayer,
+    NotAwaitingAcceptance,
+    NotCancellable,
+    InsufficientDeposit(Balance),
+    TokenNotAccepted,
+    IncorrectTransferMessage,
+    WrongStakeToken,
+    StakeInTokens,
+    NotEnoughTokens,
+    NothingToWithdraw,
+    InsufficientStorageDeposit(Balance),
+    ZeroStake,
+
+    ChallengeNotFound,
+    NotChallenger,
+    SelfChallenge,
+    ChallengeForAnotherAccount,
+
+    NoRoketoAccount,
+
+    NotDarkGame,
+    PositionHidden,
+    MoveNotCommitted,
+    CommitmentMismatch,
+    IncorrectHash,
+    RevealStarted,
+    RevealNotAllowed,
+    RevealRequired,
+    DarkGameFromPosition,
+}
+
+impl HexError {
+    pub fn code(&self) -> u32 {
+        match self {
+            HexError::FieldTooBig => 100,
+            HexError::CellOutOfBounds => 101,
+            HexError::CellOccupied => 102,
+            HexError::IncorrectCellValue => 103,
+            HexError::BoardTooBigToEvaluate => 104,
+            HexError::EmptyField => 105,
+
+            HexError::GameNotFound => 200,
+            HexError::GameFinished => 201,
+            HexError::GameNotAccepted => 202,
+            HexError::IncorrectPredecessor => 203,
+            HexError::NotYourTurn => 204,
+            HexError::IncorrectPlayerNumber => 205,
+            HexError::IncorrectMoveArgs => 206,
+            HexError::SwapNotAllowed => 207,
+            HexError::SwapOnRectangularBoard => 208,
+            HexError::NoTimeLimit => 209,
+            HexError::NotTimedOut => 210,
+            HexError::NotEnoughMoves => 211,
+            HexError::OpeningNotAllowed => 212,
+            HexError::NoOpeningCells => 213,
+            HexError::IncorrectTimeControl => 214,
+            HexError::TimeIsUp => 215,
+
+            HexError::IncorrectNotation => 300,
+            HexError::InvalidRecord(_) => 301,
+            HexError::PositionAlreadyWon => 302,
+            HexError::MovesOutOfOrder => 303,
+            HexError::SizeMismatch => 304,
+
+            HexError::SamePlayersForStake => 400,
+            HexError::NotAPlayer => 401,
+            HexError::NotAwaitingAcceptance => 402,
+            HexError::NotCancellable => 403,
+            HexError::InsufficientDeposit(_) => 404,
+            HexError::TokenNotAccepted => 405,
+            HexError::IncorrectTransferMessage => 406,
+            HexError::WrongStakeToken => 407,
+            HexError::StakeInTokens => 408,
+            HexError::NotEnoughTokens => 409,
+            HexError::NothingToWithdraw => 410,
+            HexError::InsufficientStorageDeposit(_) => 411,
+            HexError::ZeroStake => 412,
+
+            HexError::ChallengeNotFound => 500,
+            HexError::NotChallenger => 501,
+            HexError::SelfChallenge => 502,
+            HexError::ChallengeForAnotherAccount => 503,
+
+            HexError::NoRoketoAccount => 600,
+
+            HexError::NotDarkGame => 700,
+            HexError::PositionHidden => 701,
+            HexError::MoveNotCommitted => 702,
+            HexError::CommitmentMismatch => 703,
+            HexError::IncorrectHash => 704,
+            // 705 was `NoBoardView`, retired with the stored hidden boards.
+            HexError::RevealStarted => 706,
+            HexError::RevealNotAllowed => 707,
+            HexError::RevealRequired => 708,
+            HexError::DarkGameFromPosition => 709,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            HexError::FieldTooBig => format!(
+                "The size of the field must be less or equal {}",
+                MAX_FIELD_SIZE
+            ),
+            HexError::CellOutOfBounds => "Cell is out of bounds.".to_string(),
+            HexError::CellOccupied => "Cell is already filled.".to_string(),
+            HexError::IncorrectCellValue => "Value is too big.".to_string(),
+            HexError::BoardTooBigToEvaluate => format!(
+                "Positions can be evaluated on boards up to {0}x{0}.",
+                MAX_EVALUATED_FIELD_SIZE
+            ),
+            HexError::EmptyField => "The field must have at least one row and column.".to_string(),
+
+            HexError::GameNotFound => "Game doesn't exist.".to_string(),
+            HexError::GameFinished => "Game is already finished!".to_string(),
+            HexError::GameNotAccepted => "Game isn't accepted by the opponent yet.".to_string(),
+            HexError::IncorrectPredecessor => "Incorrect predecessor account".to_string(),
+            HexError::NotYourTurn => "It's another player turn now.".to_string(),
+            HexError::IncorrectPlayerNumber => "Incorrect player number.".to_string(),
+            HexError::IncorrectMoveArgs => "Incorrect move args".to_string(),
+            HexError::SwapNotAllowed => {
+                "Swap rule can be applied only on the second player first turn".to_string()
+            }
+            HexError::SwapOnRectangularBoard => {
+                "Swap rule can be applied only on a square board".to_string()
+            }
+            HexError::NoTimeLimit => "Game has no time limit for a move.".to_string(),
+            HexError::NotTimedOut => "Opponent still has time to make a move.".to_string(),
+            HexError::NotEnoughMoves => "Game doesn't have so many moves.".to_string(),
+            HexError::OpeningNotAllowed => {
+                "The first move must be one of the opening cells.".to_string()
+            }
+            HexError::NoOpeningCells => "The opening has no empty cells.".to_string(),
+            HexError::IncorrectTimeControl => format!(
+                "Fischer clock needs main time, byo-yomi needs periods of some length, \
+                 every time is at most {} ms.",
+                MAX_CLOCK_TIME
+            ),
+            HexError::TimeIsUp => "Time is up, the opponent can claim the win.".to_string(),
+
+            HexError::IncorrectNotation => "Incorrect move notation.".to_string(),
+            HexError::InvalidRecord(reason) => format!("Invalid game record: {}", reason),
+            HexError::PositionAlreadyWon => "Position is already won.".to_string(),
+            HexError::MovesOutOfOrder => "Moves must alternate between players.".to_string(),
+            HexError::SizeMismatch => {
+                "Board size doesn't match the size of the record.".to_string()
+            }
+
+            HexError::SamePlayersForStake => {
+                "Players must be different to play for a stake.".to_string()
+            }
+            HexError::NotAPlayer => "Only a player can create a game.".to_string(),
+            HexError::NotAwaitingAcceptance => {
+                "Game isn't waiting for this account to accept it.".to_string()
+            }
+            HexError::NotCancellable => {
+                "Only games that aren't accepted yet can be cancelled.".to_string()
+            }
+            HexError::InsufficientDeposit(required) => format!(
+                "Must attach {} yoctoNEAR to cover storage and stake",
+                required
+            ),
+            HexError::TokenNotAccepted => "Token is not accepted for stakes.".to_string(),
+            HexError::IncorrectTransferMessage => "Incorrect transfer message.".to_string(),
+            HexError::WrongStakeToken => "Stake is in another token.".to_string(),
+            HexError::StakeInTokens => {
+                "Stake is in fungible tokens, use ft_transfer_call to accept it.".to_string()
+            }
+            HexError::NotEnoughTokens => "Not enough tokens to cover the stake.".to_string(),
+            HexError::NothingToWithdraw => "Nothing to withdraw.".to_string(),
+            HexError::InsufficientStorageDeposit(required) => format!(
+                "Storage deposit of {} yoctoNEAR is required, see storage_deposit",
+                required
+            ),
+            HexError::ZeroStake => "Stake in tokens must be positive.".to_string(),
+
+            HexError::ChallengeNotFound => "Challenge doesn't exist.".to_string(),
+            HexError::NotChallenger => "Only the challenger can cancel the challenge.".to_string(),
+            HexError::SelfChallenge => "Can't challenge yourself.".to_string(),
+            HexError::ChallengeForAnotherAccount => {
+                "Challenge is addressed to another account.".to_string()
+            }
+
+            HexError::NoRoketoAccount => "No Roketo account to check premium.".to_string(),
+
+            HexError::NotDarkGame => "Game isn't played with hidden counters.".to_string(),
+            HexError::PositionHidden => {
+                "Position of a dark game is hidden until the moves are revealed.".to_string()
+            }
+            HexError::MoveNotCommitted => {
+                "Moves of a dark game are made with commit_move.".to_string()
+            }
+            HexError::CommitmentMismatch => {
+                "Revealed moves don't match the committed hashes.".to_string()
+            }
+            HexError::IncorrectHash => "Hash must be 64 hexadecimal digits.".to_string(),
+            HexError::RevealStarted => {
+                "Moves are being revealed, no more moves can be committed.".to_string()
+            }
+            HexError::RevealNotAllowed => {
+                "Moves are revealed by the player waiting for a move, then by the opponent."
+                    .to_string()
+            }
+            HexError::RevealRequired => {
+                "Moves of a dark game must be revealed before it's finished.".to_string()
+            }
+            HexError::DarkGameFromPosition => {
+                "Dark games can't start from a position, its counters are public.".to_string()
+            }
+        }
+    }
+}
+
+impl From<hex_core::Error> for HexError {
+    fn from(error: hex_core::Error) -> Self {
+        match error {
+            hex_core::Error::FieldTooBig => HexError::FieldTooBig,
+            hex_core::Error::EmptyField => HexError::EmptyField,
+            hex_core::Error::CellOutOfBounds => HexError::CellOutOfBounds,
+            hex_core::Error::CellOccupied => HexError::CellOccupied,
+            hex_core::Error::IncorrectCellValue => HexError::IncorrectCellValue,
+            hex_core::Error::GameFinished => HexError::GameFinished,
+            hex_core::Error::NotYourTurn => HexError::NotYourTurn,
+            hex_core::Error::SwapNotAllowed => HexError::SwapNotAllowed,
+            hex_core::Error::SwapOnRectangularBoard => HexError::SwapOnRectangularBoard,
+        }
+    }
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "E{}: {}", self.code(), self.message())
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod error_tests {
+    use super::HexError;
+
+    #[test]
+    fn test_error_display() {
+        assert_eq!(
+            HexError::CellOccupied.to_string(),
+            "E102: Cell is already filled."
+        );
+        assert_eq!(
+            HexError::InvalidRecord("Variations aren't supported.".to_string()).to_string(),
+            "E301: Invalid game record: Variations aren't supported."
+        );
+        assert_eq!(
+            HexError::FieldTooBig.to_string(),
+            "E100: The size of the field must be less or equal 32"
+        );
+        // 705 is retired.
+        assert!(HexError::RevealNotAllowed.to_string().starts_with("E707: "));
+    }
+}
@@ -0,0 +1,276 @@
+use hex_core::Cell;
+use near_sdk::json_types::U128;
+use near_sdk::serde::Serialize;
+use near_sdk::{env, AccountId};
+
+use crate::challenge::ChallengeIndex;
+use crate::game::{FinishReason, Game, GameIndex};
+use crate::rating::Rating;
+
+pub const EVENT_STANDARD: &str = "hex_game";
+pub const EVENT_VERSION: &str = "1.0.0";
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum HexGameEvent<'a> {
+    GameCreated {
+        game_id: GameIndex,
+        first_player: &'a AccountId,
+        second_player: &'a AccountId,
+        width: usize,
+        height: usize,
+        turn: usize,
+        stake: U128,
+        stake_token: Option<&'a AccountId>,
+    },
+    GameAccepted {
+        game_id: GameIndex,
+        account_id: &'a AccountId,
+    },
+    MoveMade {
+        game_id: GameIndex,
+        player: &'a AccountId,
+        cell: &'a Cell,
+        notation: String,
+        ply: u64,
+    },
+    Swap {
+        game_id: GameIndex,
+        player: &'a AccountId,
+        cell: &'a Cell,
+        notation: String,
+        ply: u64,
+    },
+    // Dark Hex move, the cell stays hidden until the moves are revealed.
+    HiddenMoveMade {
+        game_id: GameIndex,
+        player: &'a AccountId,
+        ply: u64,
+    },
+    MovesRevealed {
+        game_id: GameIndex,
+        player: &'a AccountId,
+    },
+    GameFinished {
+        game_id: GameIndex,
+        winner: Option<&'a AccountId>,
+        reason: &'a FinishReason,
+    },
+    ChallengePosted {
+        challenge_id: ChallengeIndex,
+        challenger: &'a AccountId,
+        opponent: Option<&'a AccountId>,
+        width: usize,
+        height: usize,
+        stake: U128,
+        stake_token: Option<&'a AccountId>,
+    },
+    ChallengeAccepted {
+        challenge_id: ChallengeIndex,
+        account_id: &'a AccountId,
+        game_id: GameIndex,
+    },
+    ChallengeCancelled {
+        challenge_id: ChallengeIndex,
+    },
+    RatingUpdated {
+        field_size: usize,
+        account_id: &'a AccountId,
+        rating: &'a Rating,
+    },
+    StakeUnclaimed {
+        account_id: &'a AccountId,
+        token_id: &'a AccountId,
+        amount: U128,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct EventLog<'a> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event: &'a HexGameEvent<'a>,
+}
+
+impl<'a> HexGameEvent<'a> {
+    pub fn game_created(game_id: GameIndex, game: &'a Game) -> Self {
+        HexGameEvent::GameCreated {
+            game_id,
+            first_player: &game.first_player,
+            second_player: &game.second_player,
+            width: game.state.board.width,
+            height: game.state.board.height,
+            turn: game.state.turn,
+            stake: game.stake,
+            stake_token: game.stake_token.as_ref(),
+        }
+    }
+
+    pub fn to_event_string(&self) -> String {
+        let log = EventLog {
+            standard: EVENT_STANDARD,
+            version: EVENT_VERSION,
+            event: self,
+        };
+        format!(
+            "EVENT_JSON:{}",
+            near_sdk::serde_json::to_string(&log).expect("Failed to serialize the event.")
+        )
+    }
+
+    pub fn emit(&self) {
+        env::log_str(&self.to_event_string());
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod events_tests {
+    use near_sdk::{
+        json_types::U128,
+        serde_json::{json, Value},
+        test_utils::{accounts, get_logs, VMContextBuilder},
+        testing_env, AccountId, ONE_NEAR,
+    };
+
+    use super::HexGameEvent;
+    use hex_core::Cell;
+
+    use crate::{game::FinishReason, Contract, MoveType};
+
+    fn get_context(account: AccountId) -> near_sdk::VMContext {
+        VMContextBuilder::new()
+            .predecessor_account_id(account)
+            .attached_deposit(10 * ONE_NEAR)
+            .build()
+    }
+
+    fn parse_events(logs: Vec<String>) -> Vec<Value> {
+        logs.into_iter()
+            .filter_map(|log| {
+                log.strip_prefix("EVENT_JSON:")
+                    .map(|x| near_sdk::serde_json::from_str(x).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_event_string() {
+        let event = HexGameEvent::MoveMade {
+            game_id: 3,
+            player: &accounts(0),
+            cell: &Cell::new(5, 5),
+            notation: "f6".to_string(),
+            ply: 2,
+        };
+        assert_eq!(
+            event.to_event_string(),
+            r#"EVENT_JSON:{"standard":"hex_game","version":"1.0.0","event":"move_made","data":{"game_id":3,"player":"alice","cell":{"x":5,"y":5},"notation":"f6","ply":2}}"#
+        );
+
+        let event = HexGameEvent::GameFinished {
+            game_id: 0,
+            winner: None,
+            reason: &FinishReason::Cancellation,
+        };
+        assert_eq!(
+            event.to_event_string(),
+            r#"EVENT_JSON:{"standard":"hex_game","version":"1.0.0","event":"game_finished","data":{"game_id":0,"winner":null,"reason":"Cancellation"}}"#
+        );
+    }
+
+    #[test]
+    fn test_game_events() {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        let id = contract
+            .create_game(accounts(0), accounts(1), Some(2), None, None, None)
+            .unwrap();
+        let events = parse_events(get_logs());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["standard"], "hex_game");
+        assert_eq!(events[0]["event"], "game_created");
+        assert_eq!(events[0]["data"]["game_id"], id);
+        assert_eq!(events[0]["data"]["width"], 2);
+
+        contract.get_game(id);
+        assert_eq!(get_logs().len(), 1);
+
+        testing_env!(get_context(accounts(1)));
+        contract.accept_game(id).unwrap();
+        let events = parse_events(get_logs());
+        assert_eq!(events[0]["event"], "game_accepted");
+        assert_eq!(events[0]["data"]["account_id"], "bob");
+
+        testing_env!(get_context(accounts(0)));
+        contract.make_move_notation(id, "b1".to_string()).unwrap();
+        let events = parse_events(get_logs());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["event"], "move_made");
+        assert_eq!(
+            events[0]["data"],
+            json!({"game_id": id, "player": "alice", "cell": {"x": 1, "y": 0}, "notation": "b1", "ply": 0})
+        );
+
+        testing_env!(get_context(accounts(1)));
+        contract.make_move(id, MoveType::SWAP, None).unwrap();
+        let events = parse_events(get_logs());
+        assert_eq!(events[0]["event"], "swap");
+        assert_eq!(events[0]["data"]["notation"], "a2");
+        assert_eq!(events[0]["data"]["ply"], 1);
+
+        testing_env!(get_context(accounts(0)));
+        contract.make_move_notation(id, "a1".to_string()).unwrap();
+        testing_env!(get_context(accounts(1)));
+        contract.make_move_notation(id, "b2".to_string()).unwrap();
+        let events = parse_events(get_logs());
+        let names: Vec<&Value> = events.iter().map(|e| &e["event"]).collect();
+        assert_eq!(
+            names,
+            vec![
+                "move_made",
+                "game_finished",
+                "rating_updated",
+                "rating_updated"
+            ]
+        );
+        assert_eq!(
+            events[1]["data"],
+            json!({"game_id": id, "winner": "bob", "reason": "Connection"})
+        );
+        assert_eq!(events[2]["data"]["account_id"], "bob");
+        assert_eq!(events[2]["data"]["rating"]["rating"], 1516);
+    }
+
+    #[test]
+    fn test_challenge_events() {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        let id = contract
+            .post_challenge(Some(5), None, None, None, Some(U128(ONE_NEAR)))
+            .unwrap();
+        let events = parse_events(get_logs());
+        assert_eq!(events[0]["event"], "challenge_posted");
+        assert_eq!(events[0]["data"]["stake"], ONE_NEAR.to_string());
+
+        testing_env!(get_context(accounts(1)));
+        let game_id = contract.accept_challenge(id).unwrap();
+        let events = parse_events(get_logs());
+        let names: Vec<&Value> = events.iter().map(|e| &e["event"]).collect();
+        assert_eq!(names, vec!["game_created", "challenge_accepted"]);
+        assert_eq!(
+            events[1]["data"],
+            json!({"challenge_id": id, "account_id": "bob", "game_id": game_id})
+        );
+
+        testing_env!(get_context(accounts(0)));
+        let id = contract
+            .post_challenge(None, None, None, None, None)
+            .unwrap();
+        contract.cancel_challenge(id).unwrap();
+        let events = parse_events(get_logs());
+        assert_eq!(events[1]["event"], "challenge_cancelled");
+    }
+}
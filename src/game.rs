@@ -1,84 +1,326 @@
+use hex_core::{Cell, GameState};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{env, require, AccountId, BlockHeight};
+use near_sdk::{env, AccountId, Balance, BlockHeight};
 
-use crate::board::Board;
-use crate::cell::Cell;
+use crate::clock::{elapsed_since, Clock, TimeControl};
+use crate::error::HexError;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub enum FinishReason {
+    Connection,
+    Resignation,
+    Timeout,
+    Cancellation,
+}
+
+#[derive(Serialize, Deserialize, PartialEq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub enum GameStatus {
+    Pending,
+    Active,
+    Finished,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Default, PartialEq, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub enum Opening {
+    // The second player may take the first counter over with `MoveType::SWAP`.
+    #[default]
+    Swap,
+    NoSwap,
+    // The first counter must be placed on one of the cells, there is no swap.
+    Balanced(Vec<Cell>),
+}
+
+// Counters placed for the weaker player before the first move.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct Handicap {
+    pub player: u8,
+    pub cells: Vec<Cell>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Default, PartialEq, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct Rules {
+    pub timeout: Option<BlockHeight>,
+    // Misère (Reverse Hex): the player who connects their borders loses.
+    #[serde(default)]
+    pub misere: bool,
+    // Dark Hex: the moves are committed as hashes and revealed by both players
+    // at the end, see `commit_move`.
+    #[serde(default)]
+    pub dark: bool,
+    #[serde(default)]
+    pub opening: Opening,
+    #[serde(default)]
+    pub handicap: Option<Handicap>,
+    // Chess clock in addition to or instead of the `timeout` of a move.
+    #[serde(default)]
+    pub clock: Option<TimeControl>,
+}
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
 #[serde(crate = "near_sdk::serde")]
 pub struct Game {
     pub first_player: AccountId,
     pub second_player: AccountId,
-    pub turn: usize,
-    pub board: Board,
+    #[serde(flatten)]
+    pub state: GameState,
     pub current_block_height: BlockHeight,
     pub prev_block_height: BlockHeight,
+    pub rules: Rules,
+    // Clocks of the players as of the last move.
+    pub clocks: Option<[Clock; 2]>,
+    // Nanoseconds, the clock of the player to move runs from this moment.
+    pub last_move_timestamp: U64,
+    pub stake: U128,
+    pub stake_token: Option<AccountId>,
+    pub pending_acceptance: Option<AccountId>,
     pub is_finished: bool,
+    pub winner: Option<AccountId>,
+    pub finish_reason: Option<FinishReason>,
 }
 
 pub type GameIndex = u64;
 
 impl Game {
-    pub fn new(first_player: AccountId, second_player: AccountId, field_size: usize) -> Self {
-        Game {
+    pub fn new(
+        first_player: AccountId,
+        second_player: AccountId,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, HexError> {
+        Ok(Game {
             first_player,
             second_player,
-            turn: 0,
-            board: Board::new(field_size),
+            state: GameState::new(width, height)?,
             current_block_height: env::block_height(),
             prev_block_height: 0,
+            rules: Rules::default(),
+            clocks: None,
+            last_move_timestamp: U64(env::block_timestamp()),
+            stake: U128(0),
+            stake_token: None,
+            pending_acceptance: None,
             is_finished: false,
+            winner: None,
+            finish_reason: None,
+        })
+    }
+
+    // Sets the rules of a new game and places the handicap counters.
+    pub fn set_rules(&mut self, rules: Rules) -> Result<(), HexError> {
+        // Otherwise a player who doesn't reveal their moves locks the game.
+        if rules.dark && rules.timeout.is_none() && rules.clock.is_none() {
+            return Err(HexError::NoTimeLimit);
         }
+        if let Opening::Balanced(cells) = &rules.opening {
+            for cell in cells {
+                self.state.board.get_cell(cell)?;
+            }
+        }
+        // Nothing is changed until all the rules are checked.
+        let mut handicap_state = None;
+        if let Some(handicap) = &rules.handicap {
+            if handicap.player != 1 && handicap.player != 2 {
+                return Err(HexError::IncorrectPlayerNumber);
+            }
+            let mut board = self.state.board.clone();
+            for cell in &handicap.cells {
+                if board.get_cell(cell)? != 0 {
+                    return Err(HexError::CellOccupied);
+                }
+                board.set_cell(cell, handicap.player)?;
+            }
+            let state = GameState::from_board(board, self.state.turn)?;
+            if state.is_finished() {
+                return Err(HexError::PositionAlreadyWon);
+            }
+            handicap_state = Some(state);
+        }
+        if let Opening::Balanced(cells) = &rules.opening {
+            // Handicap counters can take the cells of the opening.
+            let board = &handicap_state.as_ref().unwrap_or(&self.state).board;
+            if !cells.iter().any(|cell| board.get_cell(cell) == Ok(0)) {
+                return Err(HexError::NoOpeningCells);
+            }
+        }
+        if let Some(control) = &rules.clock {
+            control.validate()?;
+            self.clocks = Some([Clock::new(control), Clock::new(control)]);
+        }
+        if let Some(state) = handicap_state {
+            self.state = state;
+        }
+        self.rules = rules;
+        Ok(())
     }
 
-    pub fn place_counter(&mut self, cell: &Cell, player: u8) {
-        require!(self.board.get_cell(cell) == 0, "Cell is already filled.");
-        require!(
-            self.turn % 2 + 1 == player as usize,
-            "It's another player turn now."
-        );
-        self.board.set_cell(cell, player);
-        self.turn += 1;
-        if env::block_height() != self.current_block_height {
-            self.prev_block_height = self.current_block_height;
-            self.current_block_height = env::block_height();
+    pub fn get_player_account(&self, player: u8) -> Result<AccountId, HexError> {
+        match player {
+            1 => Ok(self.first_player.clone()),
+            2 => Ok(self.second_player.clone()),
+            _ => Err(HexError::IncorrectPlayerNumber),
         }
     }
 
-    pub fn swap_rule(&mut self) -> Cell {
-        require!(
-            self.turn == 1,
-            "Swap rule can be applied only on the second player first turn"
-        );
+    // Games are created by one of the players and wait for the opponent to
+    // accept them, with or without a stake.
+    pub fn set_stake(&mut self, creator: &AccountId, stake: Balance) -> Result<(), HexError> {
+        if *creator != self.first_player && *creator != self.second_player {
+            return Err(HexError::NotAPlayer);
+        }
+        if self.first_player == self.second_player {
+            if stake > 0 {
+                return Err(HexError::SamePlayersForStake);
+            }
+            return Ok(());
+        }
+        let opponent = if *creator == self.first_player {
+            self.second_player.clone()
+        } else {
+            self.first_player.clone()
+        };
+        self.stake = U128(stake);
+        self.pending_acceptance = Some(opponent);
+        Ok(())
+    }
+
+    pub fn accept(&mut self, account: &AccountId) -> Result<(), HexError> {
+        if self.pending_acceptance.as_ref() != Some(account) {
+            return Err(HexError::NotAwaitingAcceptance);
+        }
+        self.pending_acceptance = None;
+        self.current_block_height = env::block_height();
+        self.last_move_timestamp = U64(env::block_timestamp());
+        Ok(())
+    }
+
+    pub fn cancel(&mut self) -> Result<(), HexError> {
+        if self.pending_acceptance.is_none() {
+            return Err(HexError::NotCancellable);
+        }
+        self.pending_acceptance = None;
+        self.is_finished = true;
+        self.finish_reason = Some(FinishReason::Cancellation);
+        Ok(())
+    }
+
+    pub fn is_timed_out(&self) -> bool {
+        let move_timed_out = match self.rules.timeout {
+            Some(timeout) => {
+                env::block_height() > self.current_block_height.saturating_add(timeout)
+            }
+            None => false,
+        };
+        move_timed_out || self.is_flag_fallen()
+    }
+
+    fn is_flag_fallen(&self) -> bool {
+        match (&self.rules.clock, &self.clocks) {
+            (Some(control), Some(clocks)) => {
+                let clock = &clocks[self.state.current_player() as usize - 1];
+                clock
+                    .spend(control, elapsed_since(self.last_move_timestamp.0))
+                    .is_none()
+            }
+            _ => false,
+        }
+    }
+
+    // Clocks with the time of the player to move running until the game is
+    // finished, a fallen flag shows zero time.
+    pub fn current_clocks(&self) -> Option<[Clock; 2]> {
+        let (control, mut clocks) = (self.rules.clock.as_ref()?, self.clocks?);
+        if !self.is_finished && self.pending_acceptance.is_none() {
+            let clock = &mut clocks[self.state.current_player() as usize - 1];
+            *clock = clock
+                .spend(control, elapsed_since(self.last_move_timestamp.0))
+                .unwrap_or_default();
+        }
+        Some(clocks)
+    }
+
+    // Stops the clock of the player to move before their move is applied.
+    pub fn spend_time(&mut self) -> Result<(), HexError> {
+        let elapsed = elapsed_since(self.last_move_timestamp.0);
+        if let (Some(control), Some(clocks)) = (&self.rules.clock, &mut self.clocks) {
+            let clock = &mut clocks[self.state.current_player() as usize - 1];
+            *clock = clock
+                .spend(control, elapsed)
+                .ok_or(HexError::TimeIsUp)?
+                .add_increment(control);
+        }
+        self.last_move_timestamp = U64(env::block_timestamp());
+        Ok(())
+    }
+
+    pub fn get_current_player(&self) -> AccountId {
+        if self.state.current_player() == 1 {
+            self.first_player.clone()
+        } else {
+            self.second_player.clone()
+        }
+    }
+
+    pub fn status(&self) -> GameStatus {
+        if self.is_finished {
+            GameStatus::Finished
+        } else if self.pending_acceptance.is_some() {
+            GameStatus::Pending
+        } else {
+            GameStatus::Active
+        }
+    }
+
+    pub fn finish(&mut self, winner: AccountId, reason: FinishReason) {
+        self.is_finished = true;
+        self.winner = Some(winner);
+        self.finish_reason = Some(reason);
+    }
 
-        let non_zero_byte = self
-            .board
-            .field
-            .0
-            .iter()
-            .enumerate()
-            .find(|(_, &x)| x != 0)
-            .unwrap();
-        let mut bit_number = 8 * non_zero_byte.0;
-        if non_zero_byte.1 & 4 == 4 {
-            bit_number += 2;
-        } else if non_zero_byte.1 & 16 == 16 {
-            bit_number += 4;
-        } else if non_zero_byte.1 & 64 == 64 {
-            bit_number += 6;
-        }
-        let cell = self.board.get_coords(bit_number);
-
-        self.board.set_cell(&cell, 0);
-        self.board.set_cell(&cell.symm(), 2);
-        self.turn += 1;
+    pub fn place_counter(&mut self, cell: &Cell, player: u8) -> Result<(), HexError> {
+        if let Opening::Balanced(cells) = &self.rules.opening {
+            if self.state.turn == 0 && !cells.contains(cell) {
+                return Err(HexError::OpeningNotAllowed);
+            }
+        }
+        self.state.place(cell, player)?;
+        self.update_block_height();
+        Ok(())
+    }
+
+    pub fn swap_rule(&mut self) -> Result<Cell, HexError> {
+        // The swap would also take the handicap counters over.
+        if self.rules.opening != Opening::Swap || self.rules.handicap.is_some() {
+            return Err(HexError::SwapNotAllowed);
+        }
+        let cell = self.state.swap()?;
+        self.update_block_height();
+        Ok(cell)
+    }
+
+    // A move of Dark Hex that places nothing: a committed move or a collision
+    // with a hidden counter of the opponent.
+    pub fn lose_turn(&mut self) {
+        self.state.turn += 1;
+        self.update_block_height();
+    }
+
+    fn update_block_height(&mut self) {
         if env::block_height() != self.current_block_height {
             self.prev_block_height = self.current_block_height;
             self.current_block_height = env::block_height();
         }
-
-        cell
     }
 }
 
@@ -89,113 +331,253 @@ mod game_tests {
         testing_env,
     };
 
-    use crate::cell::Cell;
+    use hex_core::Cell;
+
+    use crate::error::HexError;
 
-    use super::Game;
+    use super::{Game, Handicap, Opening, Rules};
 
     fn get_context() -> VMContextBuilder {
         VMContextBuilder::new()
     }
 
     #[test]
-    #[should_panic]
-    fn test_place_counter_wrong_player_1() {
-        let mut game = Game::new(accounts(0), accounts(1), 11);
-        game.place_counter(&Cell::new(1, 1), 2);
+    fn test_place_counter() {
+        testing_env!(get_context().block_index(0).build());
+
+        let mut game = Game::new(accounts(0), accounts(1), 11, 11).unwrap();
+        game.place_counter(&Cell::new(1, 1), 1).unwrap();
+        game.place_counter(&Cell::new(1, 2), 2).unwrap();
+        game.place_counter(&Cell::new(10, 7), 1).unwrap();
+        assert_eq!(game.current_block_height, 0);
+        assert_eq!(game.prev_block_height, 0);
+
+        testing_env!(get_context().block_index(100).build());
+        game.place_counter(&Cell::new(5, 9), 2).unwrap();
+        game.place_counter(&Cell::new(3, 7), 1).unwrap();
+        assert_eq!(game.current_block_height, 100);
+        assert_eq!(game.prev_block_height, 0);
+
+        assert_eq!(game.state.turn, 5);
+        assert_eq!(game.get_current_player(), accounts(1));
+        assert_eq!(
+            game.place_counter(&Cell::new(5, 9), 2),
+            Err(HexError::CellOccupied)
+        );
+    }
+
+    #[test]
+    fn test_is_timed_out() {
+        testing_env!(get_context().block_index(10).build());
+        let mut game = Game::new(accounts(0), accounts(1), 11, 11).unwrap();
+        assert!(!game.is_timed_out());
+
+        game.rules.timeout = Some(5);
+        testing_env!(get_context().block_index(15).build());
+        assert!(!game.is_timed_out());
+
+        game.place_counter(&Cell::new(1, 1), 1).unwrap();
+        testing_env!(get_context().block_index(20).build());
+        assert!(!game.is_timed_out());
+
+        testing_env!(get_context().block_index(21).build());
+        assert!(game.is_timed_out());
+
+        game.rules.timeout = Some(u64::MAX);
+        testing_env!(get_context().block_index(u64::MAX).build());
+        assert!(!game.is_timed_out());
     }
 
     #[test]
-    #[should_panic]
-    fn test_place_counter_wrong_player_2() {
-        let mut game = Game::new(accounts(0), accounts(1), 11);
-        game.place_counter(&Cell::new(1, 1), 1);
-        game.place_counter(&Cell::new(2, 1), 1);
+    fn test_set_stake_and_accept() {
+        testing_env!(get_context().block_index(10).build());
+        let mut game = Game::new(accounts(0), accounts(1), 11, 11).unwrap();
+        game.set_stake(&accounts(1), 100).unwrap();
+        assert_eq!(game.stake.0, 100);
+        assert_eq!(game.pending_acceptance, Some(accounts(0)));
+
+        testing_env!(get_context().block_index(30).build());
+        game.accept(&accounts(0)).unwrap();
+        assert_eq!(game.pending_acceptance, None);
+        assert_eq!(game.current_block_height, 30);
     }
 
     #[test]
-    #[should_panic]
-    fn test_place_counter_cell_is_already_filled() {
-        let mut game = Game::new(accounts(0), accounts(1), 11);
-        game.place_counter(&Cell::new(1, 1), 1);
-        game.place_counter(&Cell::new(1, 1), 2);
+    fn test_set_stake_not_a_player() {
+        let mut game = Game::new(accounts(0), accounts(1), 11, 11).unwrap();
+        assert_eq!(game.set_stake(&accounts(2), 100), Err(HexError::NotAPlayer));
     }
 
     #[test]
-    fn test_place_counter() {
+    fn test_set_stake_same_players() {
+        let mut game = Game::new(accounts(0), accounts(0), 11, 11).unwrap();
+        assert_eq!(
+            game.set_stake(&accounts(0), 100),
+            Err(HexError::SamePlayersForStake)
+        );
+        game.set_stake(&accounts(0), 0).unwrap();
+        assert_eq!(game.pending_acceptance, None);
+    }
+
+    #[test]
+    fn test_accept_by_creator() {
+        let mut game = Game::new(accounts(0), accounts(1), 11, 11).unwrap();
+        game.set_stake(&accounts(0), 100).unwrap();
+        assert_eq!(
+            game.accept(&accounts(0)),
+            Err(HexError::NotAwaitingAcceptance)
+        );
+    }
+
+    #[test]
+    fn test_swap_rule() {
         testing_env!(get_context().block_index(0).build());
+        let mut game = Game::new(accounts(0), accounts(1), 11, 11).unwrap();
+        game.place_counter(&Cell::new(10, 7), 1).unwrap();
 
-        let mut game = Game::new(accounts(0), accounts(1), 11);
-        game.place_counter(&Cell::new(1, 1), 1);
-        game.place_counter(&Cell::new(1, 2), 2);
-        game.place_counter(&Cell::new(10, 7), 1);
-        assert_eq!(game.current_block_height, 0);
-        assert_eq!(game.prev_block_height, 0);
+        testing_env!(get_context().block_index(7).build());
+        assert_eq!(game.swap_rule(), Ok(Cell::new(10, 7)));
+        assert_eq!(game.state.board.get_cell(&Cell::new(7, 10)), Ok(2));
+        assert_eq!(game.current_block_height, 7);
+        assert_eq!(game.swap_rule(), Err(HexError::SwapNotAllowed));
+    }
 
-        testing_env!(get_context().block_index(100).build());
-        game.place_counter(&Cell::new(5, 9), 2);
-        game.place_counter(&Cell::new(3, 7), 1);
-        assert_eq!(game.current_block_height, 100);
-        assert_eq!(game.prev_block_height, 0);
+    #[test]
+    fn test_handicap() {
+        let mut game = Game::new(accounts(0), accounts(1), 5, 5).unwrap();
+        let rules = Rules {
+            handicap: Some(Handicap {
+                player: 2,
+                cells: vec![Cell::new(2, 2), Cell::new(1, 3)],
+            }),
+            ..Rules::default()
+        };
+        game.set_rules(rules.clone()).unwrap();
+        assert_eq!(game.rules, rules);
+        assert_eq!(game.state.board.get_cell(&Cell::new(1, 3)), Ok(2));
+        assert_eq!(game.state.turn, 0);
+        assert_eq!(game.get_current_player(), accounts(0));
 
-        assert_eq!(game.first_player, accounts(0));
-        assert_eq!(game.second_player, accounts(1));
-        assert_eq!(game.turn, 5);
-        for i in 0..11 {
-            for j in 0..11 {
-                let cell = Cell::new(i, j);
-                if cell == Cell::new(1, 1) || cell == Cell::new(10, 7) || cell == Cell::new(3, 7) {
-                    assert_eq!(game.board.get_cell(&cell), 1);
-                } else if cell == Cell::new(1, 2) || cell == Cell::new(5, 9) {
-                    assert_eq!(game.board.get_cell(&cell), 2);
-                } else {
-                    assert_eq!(game.board.get_cell(&cell), 0);
-                }
-            }
-        }
+        // The handicap counters are connected with the placed ones.
+        game.place_counter(&Cell::new(0, 0), 1).unwrap();
+        assert_eq!(game.swap_rule(), Err(HexError::SwapNotAllowed));
+        game.place_counter(&Cell::new(0, 3), 2).unwrap();
+        game.place_counter(&Cell::new(0, 1), 1).unwrap();
+        game.place_counter(&Cell::new(3, 2), 2).unwrap();
+        game.place_counter(&Cell::new(0, 2), 1).unwrap();
+        game.place_counter(&Cell::new(4, 2), 2).unwrap();
+        assert_eq!(game.state.winner, Some(2));
     }
 
     #[test]
-    #[should_panic]
-    fn test_swap_rule_too_early() {
-        let mut game = Game::new(accounts(0), accounts(1), 11);
-        game.swap_rule();
+    fn test_incorrect_handicap() {
+        let handicap = |player, cells: &[(usize, usize)]| Rules {
+            handicap: Some(Handicap {
+                player,
+                cells: cells.iter().map(|&(x, y)| Cell::new(x, y)).collect(),
+            }),
+            ..Rules::default()
+        };
+        let mut game = Game::new(accounts(0), accounts(1), 3, 3).unwrap();
+        assert_eq!(
+            game.set_rules(handicap(3, &[(1, 1)])),
+            Err(HexError::IncorrectPlayerNumber)
+        );
+        assert_eq!(
+            game.set_rules(handicap(1, &[(1, 3)])),
+            Err(HexError::CellOutOfBounds)
+        );
+        assert_eq!(
+            game.set_rules(handicap(1, &[(1, 1), (1, 1)])),
+            Err(HexError::CellOccupied)
+        );
+        assert_eq!(
+            game.set_rules(handicap(1, &[(1, 0), (1, 1), (1, 2)])),
+            Err(HexError::PositionAlreadyWon)
+        );
+        assert_eq!(game.state.board.get_cell(&Cell::new(1, 1)), Ok(0));
+        assert_eq!(game.rules, Rules::default());
     }
 
     #[test]
-    #[should_panic]
-    fn test_swap_rule_too_late() {
-        let mut game = Game::new(accounts(0), accounts(1), 11);
-        game.place_counter(&Cell::new(2, 5), 1);
-        game.place_counter(&Cell::new(10, 7), 2);
-        game.swap_rule();
+    fn test_opening_rules() {
+        let mut game = Game::new(accounts(0), accounts(1), 5, 5).unwrap();
+        game.set_rules(Rules {
+            opening: Opening::NoSwap,
+            ..Rules::default()
+        })
+        .unwrap();
+        game.place_counter(&Cell::new(2, 2), 1).unwrap();
+        assert_eq!(game.swap_rule(), Err(HexError::SwapNotAllowed));
+
+        let mut game = Game::new(accounts(0), accounts(1), 5, 5).unwrap();
+        game.set_rules(Rules {
+            opening: Opening::Balanced(vec![Cell::new(0, 4), Cell::new(4, 0)]),
+            ..Rules::default()
+        })
+        .unwrap();
+        assert_eq!(
+            game.place_counter(&Cell::new(2, 2), 1),
+            Err(HexError::OpeningNotAllowed)
+        );
+        game.place_counter(&Cell::new(4, 0), 1).unwrap();
+        assert_eq!(game.swap_rule(), Err(HexError::SwapNotAllowed));
+        game.place_counter(&Cell::new(2, 2), 2).unwrap();
+        game.place_counter(&Cell::new(2, 1), 1).unwrap();
+
+        let mut game = Game::new(accounts(0), accounts(1), 5, 5).unwrap();
+        assert_eq!(
+            game.set_rules(Rules {
+                opening: Opening::Balanced(vec![]),
+                ..Rules::default()
+            }),
+            Err(HexError::NoOpeningCells)
+        );
+        assert_eq!(
+            game.set_rules(Rules {
+                opening: Opening::Balanced(vec![Cell::new(5, 0)]),
+                ..Rules::default()
+            }),
+            Err(HexError::CellOutOfBounds)
+        );
+        let handicap = Handicap {
+            player: 1,
+            cells: vec![Cell::new(0, 4), Cell::new(4, 0)],
+        };
+        assert_eq!(
+            game.set_rules(Rules {
+                opening: Opening::Balanced(vec![Cell::new(4, 0), Cell::new(0, 4)]),
+                handicap: Some(handicap.clone()),
+                ..Rules::default()
+            }),
+            Err(HexError::NoOpeningCells)
+        );
+        game.set_rules(Rules {
+            opening: Opening::Balanced(vec![Cell::new(4, 0), Cell::new(2, 2)]),
+            handicap: Some(handicap),
+            ..Rules::default()
+        })
+        .unwrap();
+        assert_eq!(
+            game.place_counter(&Cell::new(4, 0), 1),
+            Err(HexError::CellOccupied)
+        );
+        game.place_counter(&Cell::new(2, 2), 1).unwrap();
     }
 
     #[test]
-    fn test_swap_rule() {
-        let mut game = Game::new(accounts(0), accounts(1), 11);
-        game.place_counter(&Cell::new(10, 7), 1);
-
-        let c = game.swap_rule();
-        assert_eq!(Cell::new(10, 7), c);
-        assert_eq!(0, game.board.get_cell(&Cell::new(10, 7)));
-        assert_eq!(2, game.board.get_cell(&Cell::new(7, 10)));
-
-        game.place_counter(&Cell::new(1, 1), 1);
-        game.place_counter(&Cell::new(5, 9), 2);
-        game.place_counter(&Cell::new(3, 7), 1);
-
-        assert_eq!(game.turn, 5);
-        for i in 0..11 {
-            for j in 0..11 {
-                let cell = Cell::new(i, j);
-                if cell == Cell::new(1, 1) || cell == Cell::new(3, 7) {
-                    assert_eq!(game.board.get_cell(&cell), 1);
-                } else if cell == Cell::new(7, 10) || cell == Cell::new(5, 9) {
-                    assert_eq!(game.board.get_cell(&cell), 2);
-                } else {
-                    assert_eq!(game.board.get_cell(&cell), 0);
-                }
-            }
-        }
+    fn test_dark_rules_need_time_limit() {
+        let mut game = Game::new(accounts(0), accounts(1), 5, 5).unwrap();
+        let rules = Rules {
+            dark: true,
+            ..Rules::default()
+        };
+        assert_eq!(game.set_rules(rules.clone()), Err(HexError::NoTimeLimit));
+        game.set_rules(Rules {
+            timeout: Some(100),
+            ..rules
+        })
+        .unwrap();
+        assert!(game.rules.dark);
     }
 }
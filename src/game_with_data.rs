@@ -1,111 +1,313 @@
+use hex_core::Board;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::{env, require, AccountId};
-use std::collections::VecDeque;
+use near_sdk::collections::Vector;
+use near_sdk::{env, AccountId};
 
-use crate::board::Board;
-use crate::cell::Cell;
-use crate::game::Game;
+use crate::dark::{move_hash, DarkState, RevealedMove};
+use crate::error::HexError;
+use crate::game::{FinishReason, Game, GameIndex, Opening};
+use crate::history::{encode_cell, encode_collision, encode_resign, Move, SWAP_MOVE};
 use crate::*;
 
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct GameWithData {
     pub game: Game,
-    pub data: Board,
+    pub moves: Vector<u16>,
+    pub dark: DarkState,
 }
 
 impl GameWithData {
-    pub fn new(first_player: AccountId, second_player: AccountId, field_size: usize) -> Self {
-        Self {
-            game: Game::new(first_player, second_player, field_size),
-            data: Board::new(field_size),
+    pub fn new(
+        index: GameIndex,
+        first_player: AccountId,
+        second_player: AccountId,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, HexError> {
+        Ok(Self {
+            game: Game::new(first_player, second_player, width, height)?,
+            moves: Vector::new(StorageKey::Moves { game_id: index }),
+            dark: DarkState::default(),
+        })
+    }
+
+    // Moves of a dark game are only committed until both players reveal them.
+    fn is_revealed(&self) -> bool {
+        !self.game.rules.dark || self.dark.revealed.len() == 2
+    }
+
+    // Committed moves stay hidden also when the game is lost on time before
+    // the opponent has revealed them.
+    fn is_position_hidden(&self) -> bool {
+        !self.is_revealed() && !self.dark.commitments.is_empty()
+    }
+
+    pub fn check_position_visible(&self) -> Result<(), HexError> {
+        if self.is_position_hidden() {
+            return Err(HexError::PositionHidden);
         }
+        Ok(())
     }
 
-    pub fn make_move(&mut self, move_type: MoveType, cell: Option<Cell>) {
+    // Returns the cell of the counter placed by the move.
+    pub fn make_move(
+        &mut self,
+        move_type: MoveType,
+        cell: Option<Cell>,
+    ) -> Result<Option<Cell>, HexError> {
+        if self.game.rules.dark {
+            match move_type {
+                MoveType::PLACE if !self.is_revealed() => return Err(HexError::MoveNotCommitted),
+                // The moves must be checked against the commitments first.
+                MoveType::RESIGN if self.is_position_hidden() => {
+                    return Err(HexError::RevealRequired)
+                }
+                MoveType::SWAP => return Err(HexError::SwapNotAllowed),
+                _ => {}
+            }
+        }
         match (move_type, cell) {
             (MoveType::PLACE, Some(cell)) => {
-                if self.game.turn % 2 == 0 {
-                    require!(
-                        env::predecessor_account_id() == self.game.first_player,
-                        "Incorrect predecessor account"
-                    );
-                    self.game.place_counter(&cell, 1);
-                } else {
-                    require!(
-                        env::predecessor_account_id() == self.game.second_player,
-                        "Incorrect predecessor account"
-                    );
-                    self.game.place_counter(&cell, 2);
+                let player = (self.game.state.turn % 2) as u8 + 1;
+                if env::predecessor_account_id() != self.game.get_player_account(player)? {
+                    return Err(HexError::IncorrectPredecessor);
                 }
-                self.process_cell(cell);
+                self.game.spend_time()?;
+                self.place(cell.clone(), player)?;
+                Ok(Some(cell))
             }
             (MoveType::SWAP, _) => {
-                require!(
-                    env::predecessor_account_id() == self.game.second_player,
-                    "Incorrect predecessor account"
-                );
-                let cell = self.game.swap_rule();
-                self.data.set_cell(&cell, 0);
-                self.process_cell(cell.symm());
+                if env::predecessor_account_id() != self.game.second_player {
+                    return Err(HexError::IncorrectPredecessor);
+                }
+                self.game.spend_time()?;
+                self.swap().map(Some)
             }
-            _ => env::panic_str("Incorrect move args"),
+            (MoveType::RESIGN, _) => {
+                let account = env::predecessor_account_id();
+                let (player, winner) = if account == self.game.first_player {
+                    (1, self.game.second_player.clone())
+                } else if account == self.game.second_player {
+                    (2, self.game.first_player.clone())
+                } else {
+                    return Err(HexError::IncorrectPredecessor);
+                };
+                self.moves.push(&encode_resign(player));
+                self.game.finish(winner, FinishReason::Resignation);
+                Ok(None)
+            }
+            _ => Err(HexError::IncorrectMoveArgs),
+        }
+    }
+
+    // The turn passes without anything placed on the board, the position is
+    // rebuilt when both players have revealed their moves.
+    pub fn commit_move(&mut self, hash: Vec<u8>) -> Result<(), HexError> {
+        if !self.game.rules.dark {
+            return Err(HexError::NotDarkGame);
+        }
+        if !self.dark.revealed.is_empty() {
+            return Err(HexError::RevealStarted);
+        }
+        if env::predecessor_account_id() != self.game.get_current_player() {
+            return Err(HexError::IncorrectPredecessor);
         }
+        self.game.spend_time()?;
+        self.dark.commitments.push(hash);
+        self.game.lose_turn();
+        Ok(())
     }
 
-    fn process_cell(&mut self, cell: Cell) {
-        let color = self.game.board.get_cell(&cell);
-        let (mut border1, mut border2) = if color == 1 {
-            (cell.y == 0, cell.y + 1 == self.data.size)
+    // The player waiting for the opponent's move reveals first, then the
+    // opponent, instead of their next move. Returns the number of the player.
+    pub fn reveal_moves(&mut self, moves: &[RevealedMove]) -> Result<u8, HexError> {
+        if !self.game.rules.dark {
+            return Err(HexError::NotDarkGame);
+        }
+        let account = env::predecessor_account_id();
+        let player = if account == self.game.first_player {
+            1
+        } else if account == self.game.second_player {
+            2
         } else {
-            (cell.x == 0, cell.x + 1 == self.data.size)
+            return Err(HexError::IncorrectPredecessor);
         };
-        let neighbours = cell.get_neighbours(self.data.size);
-        let good_neighbours = neighbours
-            .iter()
-            .filter(|c| self.game.board.get_cell(c) == color);
-        border1 = border1 || good_neighbours.clone().any(|c| self.data.get_cell(c) == 1);
-        border2 = border2 || good_neighbours.clone().any(|c| self.data.get_cell(c) == 2);
-        if border1 && border2 {
-            self.game.is_finished = true;
-        } else if border1 {
-            self.bfs(cell, color, 1);
-        } else if border2 {
-            self.bfs(cell, color, 2);
+        let to_move = self.game.state.current_player() == player;
+        if self.dark.commitments.is_empty()
+            || self.dark.revealed.len() == 2
+            || to_move == self.dark.revealed.is_empty()
+        {
+            return Err(HexError::RevealNotAllowed);
+        }
+
+        let start_turn = self.check_hidden_moves(player, moves)?;
+        if to_move {
+            self.game.spend_time()?;
         }
+        self.dark
+            .revealed
+            .push((player, moves.iter().map(|mv| mv.cell.clone()).collect()));
+        if to_move {
+            self.replay_hidden_moves(start_turn)?;
+        }
+        Ok(player)
     }
 
-    fn bfs(&mut self, cell: Cell, color: u8, border: u8) {
-        self.data.set_cell(&cell, border);
-        let mut q: VecDeque<Cell> = VecDeque::new();
-        q.push_back(cell);
-        let field_size = self.data.size;
-        while !q.is_empty() {
-            let v = q.pop_front().unwrap();
-            let neighbours = v.get_neighbours(field_size);
-            let good_neighbours: Vec<Cell> = neighbours
-                .into_iter()
-                .filter(|c| self.game.board.get_cell(c) == color && self.data.get_cell(c) != border)
-                .collect();
-            if good_neighbours
-                .clone()
-                .into_iter()
-                .any(|c| self.data.get_cell(&c) != 0)
-            {
-                self.game.is_finished = true;
-                return;
+    // Checks all the committed moves of the player in the order they were
+    // made. Returns the turn of the first hidden move.
+    fn check_hidden_moves(&self, player: u8, moves: &[RevealedMove]) -> Result<usize, HexError> {
+        let start_turn = self.game.state.turn - self.dark.commitments.len();
+        let commitments: Vec<(usize, &Vec<u8>)> = (start_turn..self.game.state.turn)
+            .zip(&self.dark.commitments)
+            .filter(|(turn, _)| (turn % 2) as u8 + 1 == player)
+            .collect();
+        if commitments.len() != moves.len() {
+            return Err(HexError::CommitmentMismatch);
+        }
+        for (mv, (turn, hash)) in moves.iter().zip(commitments) {
+            if *hash != move_hash(&mv.cell, &mv.salt) {
+                return Err(HexError::CommitmentMismatch);
             }
-            for c in good_neighbours.into_iter() {
-                self.data.set_cell(&c, border);
-                q.push_back(Cell { x: c.x, y: c.y });
+            self.game.state.board.get_cell(&mv.cell)?;
+            if let Opening::Balanced(cells) = &self.game.rules.opening {
+                if turn == 0 && !cells.contains(&mv.cell) {
+                    return Err(HexError::OpeningNotAllowed);
+                }
             }
         }
+        Ok(start_turn)
+    }
+
+    // The board of the game with the hidden counters of the player, who
+    // proves them like in `reveal_moves`. Collisions aren't known until both
+    // players reveal, so moves into occupied cells are left out.
+    pub fn board_view(&self, player: u8, moves: &[RevealedMove]) -> Result<Board, HexError> {
+        if !self.game.rules.dark {
+            return Err(HexError::NotDarkGame);
+        }
+        if player != 1 && player != 2 {
+            return Err(HexError::IncorrectPlayerNumber);
+        }
+        let mut board = self.game.state.board.clone();
+        if !self.is_position_hidden() {
+            return Ok(board);
+        }
+        self.check_hidden_moves(player, moves)?;
+        for mv in moves {
+            if board.get_cell(&mv.cell)? == 0 {
+                board.set_cell(&mv.cell, player)?;
+            }
+        }
+        Ok(board)
+    }
+
+    // A move into an occupied cell, usually a counter of the opponent the
+    // player couldn't see, is a collision and the player loses the turn.
+    fn replay_hidden_moves(&mut self, start_turn: usize) -> Result<(), HexError> {
+        let mut cells = [vec![], vec![]];
+        for (player, revealed) in &self.dark.revealed {
+            cells[*player as usize - 1] = revealed.clone();
+        }
+        let mut next = [cells[0].iter(), cells[1].iter()];
+        self.game.state.turn = start_turn;
+        for _ in 0..self.dark.commitments.len() {
+            let player = self.game.state.current_player();
+            let cell = next[player as usize - 1].next().unwrap().clone();
+            if self.game.state.board.get_cell(&cell)? != 0 {
+                self.moves
+                    .push(&encode_collision(&cell, self.game.state.board.width));
+                self.game.lose_turn();
+            } else {
+                self.place(cell, player)?;
+            }
+            if self.game.is_finished {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn setup_position(&mut self, moves: &[Move]) -> Result<(), HexError> {
+        for mv in moves {
+            let player = if mv.move_type == MoveType::SWAP {
+                2
+            } else {
+                (self.game.state.turn % 2) as u8 + 1
+            };
+            if mv.player != player {
+                return Err(HexError::MovesOutOfOrder);
+            }
+            match (&mv.move_type, &mv.cell) {
+                (MoveType::PLACE, Some(cell)) => self.place(cell.clone(), player)?,
+                (MoveType::SWAP, None) => {
+                    self.swap()?;
+                }
+                _ => return Err(HexError::IncorrectMoveArgs),
+            }
+            if self.game.is_finished {
+                return Err(HexError::PositionAlreadyWon);
+            }
+        }
+        Ok(())
+    }
+
+    fn place(&mut self, cell: Cell, player: u8) -> Result<(), HexError> {
+        self.game.place_counter(&cell, player)?;
+        self.moves
+            .push(&encode_cell(&cell, self.game.state.board.width));
+        self.check_connection()
+    }
+
+    fn swap(&mut self) -> Result<Cell, HexError> {
+        let cell = self.game.swap_rule()?.symm();
+        self.moves.push(&SWAP_MOVE);
+        self.check_connection()?;
+        Ok(cell)
+    }
+
+    pub fn claim_timeout(&mut self) -> Result<(), HexError> {
+        if self.game.rules.timeout.is_none() && self.game.rules.clock.is_none() {
+            return Err(HexError::NoTimeLimit);
+        }
+        let waiting_player = 2 - (self.game.state.turn % 2) as u8;
+        let account = self.game.get_player_account(waiting_player)?;
+        if env::predecessor_account_id() != account {
+            return Err(HexError::IncorrectPredecessor);
+        }
+        if !self.game.is_timed_out() {
+            return Err(HexError::NotTimedOut);
+        }
+        // The winner of a dark game proves their moves, the moves of the
+        // opponent who didn't reveal them are lost with the game.
+        if self.is_position_hidden()
+            && !self
+                .dark
+                .revealed
+                .iter()
+                .any(|(player, _)| *player == waiting_player)
+        {
+            return Err(HexError::RevealRequired);
+        }
+        self.game.finish(account, FinishReason::Timeout);
+        Ok(())
+    }
+
+    fn check_connection(&mut self) -> Result<(), HexError> {
+        if let Some(color) = self.game.state.winner {
+            let color = if self.game.rules.misere {
+                3 - color
+            } else {
+                color
+            };
+            let winner = self.game.get_player_account(color)?;
+            self.game.finish(winner, FinishReason::Connection);
+        }
+        Ok(())
     }
 }
 
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod game_with_board_tests {
-    use std::fmt::Debug;
-
     use near_sdk::{
         test_utils::{accounts, VMContextBuilder},
         testing_env,
@@ -113,21 +315,6 @@ mod game_with_board_tests {
 
     use super::*;
 
-    impl PartialEq for Board {
-        fn eq(&self, other: &Self) -> bool {
-            self.size == other.size && self.field == other.field
-        }
-    }
-
-    impl Debug for Board {
-        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            f.debug_struct("Board")
-                .field("size", &self.size)
-                .field("field", &self.field)
-                .finish()
-        }
-    }
-
     fn get_context(account: AccountId) -> near_sdk::VMContext {
         VMContextBuilder::new()
             .predecessor_account_id(account)
@@ -135,130 +322,214 @@ mod game_with_board_tests {
     }
 
     #[test]
-    fn test_bfs() {
-        let mut test_game = GameWithData::new(accounts(0), accounts(1), 5);
-        test_game.game.board.set_cell(&Cell::new(0, 0), 1);
-        test_game.game.board.set_cell(&Cell::new(0, 1), 1);
-        test_game.game.board.set_cell(&Cell::new(0, 2), 1);
-
-        test_game.game.board.set_cell(&Cell::new(4, 4), 1);
-        test_game.game.board.set_cell(&Cell::new(3, 4), 1);
-
-        test_game.game.board.set_cell(&Cell::new(0, 3), 1);
-        test_game.game.board.set_cell(&Cell::new(1, 2), 1);
-
-        test_game.game.board.set_cell(&Cell::new(4, 0), 2);
-
-        test_game.game.board.set_cell(&Cell::new(2, 1), 1);
-
-        test_game.game.board.set_cell(&Cell::new(3, 0), 2);
+    fn test_make_move_connection_winner() {
+        let mut test_game = GameWithData::new(0, accounts(0), accounts(1), 2, 2).unwrap();
 
-        let mut test_data = Board::new(5);
-        test_data.set_cell(&Cell::new(0, 0), 2);
-        test_data.set_cell(&Cell::new(0, 1), 2);
-        test_data.set_cell(&Cell::new(0, 2), 2);
-        test_data.set_cell(&Cell::new(0, 3), 2);
-        test_data.set_cell(&Cell::new(1, 2), 2);
-        test_data.set_cell(&Cell::new(2, 1), 2);
-
-        test_game.bfs(Cell::new(0, 2), 1, 2);
-        assert_eq!(test_game.data, test_data);
-
-        test_data.set_cell(&Cell::new(4, 0), 2);
-        test_data.set_cell(&Cell::new(3, 0), 2);
+        testing_env!(get_context(accounts(0)));
+        test_game
+            .make_move(MoveType::PLACE, Some(Cell::new(0, 0)))
+            .unwrap();
+        testing_env!(get_context(accounts(1)));
+        test_game
+            .make_move(MoveType::PLACE, Some(Cell::new(1, 0)))
+            .unwrap();
+        assert!(!test_game.game.is_finished);
+        assert_eq!(test_game.game.winner, None);
 
-        test_game.bfs(Cell::new(3, 0), 2, 2);
-        assert_eq!(test_game.data, test_data);
+        testing_env!(get_context(accounts(0)));
+        test_game
+            .make_move(MoveType::PLACE, Some(Cell::new(0, 1)))
+            .unwrap();
+        assert!(test_game.game.is_finished);
+        assert_eq!(test_game.game.winner, Some(accounts(0)));
+        assert_eq!(test_game.game.finish_reason, Some(FinishReason::Connection));
+        assert_eq!(
+            test_game.game.state.winning_path,
+            Some(vec![Cell::new(0, 0), Cell::new(0, 1)])
+        );
     }
 
     #[test]
-    fn test_process_cell() {
-        let mut test_game = GameWithData::new(accounts(0), accounts(1), 5);
-        test_game.game.board.set_cell(&Cell::new(0, 0), 1);
-        test_game.game.board.set_cell(&Cell::new(0, 1), 1);
-        test_game.game.board.set_cell(&Cell::new(0, 2), 1);
-
-        test_game.game.board.set_cell(&Cell::new(4, 4), 1);
-        test_game.game.board.set_cell(&Cell::new(3, 4), 1);
-
-        test_game.game.board.set_cell(&Cell::new(0, 3), 1);
-        test_game.game.board.set_cell(&Cell::new(1, 2), 1);
+    fn test_make_move_resign() {
+        let mut test_game = GameWithData::new(0, accounts(0), accounts(1), 5, 5).unwrap();
 
-        test_game.game.board.set_cell(&Cell::new(4, 0), 2);
+        testing_env!(get_context(accounts(0)));
+        test_game
+            .make_move(MoveType::PLACE, Some(Cell::new(2, 2)))
+            .unwrap();
+        test_game.make_move(MoveType::RESIGN, None).unwrap();
+        assert!(test_game.game.is_finished);
+        assert_eq!(test_game.game.winner, Some(accounts(1)));
+        assert_eq!(
+            test_game.game.finish_reason,
+            Some(FinishReason::Resignation)
+        );
+    }
 
-        test_game.game.board.set_cell(&Cell::new(2, 1), 1);
+    #[test]
+    fn test_make_move_resign_not_a_player() {
+        let mut test_game = GameWithData::new(0, accounts(0), accounts(1), 5, 5).unwrap();
+        testing_env!(get_context(accounts(2)));
+        assert_eq!(
+            test_game.make_move(MoveType::RESIGN, None),
+            Err(HexError::IncorrectPredecessor)
+        );
+    }
 
-        test_game.game.board.set_cell(&Cell::new(3, 0), 2);
+    #[test]
+    fn test_claim_timeout() {
+        testing_env!(VMContextBuilder::new().block_index(0).build());
+        let mut test_game = GameWithData::new(0, accounts(0), accounts(1), 5, 5).unwrap();
+        test_game.game.rules.timeout = Some(10);
 
-        let mut test_data = Board::new(5);
-        test_data.set_cell(&Cell::new(0, 0), 1);
-        test_data.set_cell(&Cell::new(0, 1), 1);
-        test_data.set_cell(&Cell::new(0, 2), 1);
-        test_data.set_cell(&Cell::new(0, 3), 1);
-        test_data.set_cell(&Cell::new(1, 2), 1);
-        test_data.set_cell(&Cell::new(2, 1), 1);
+        testing_env!(get_context(accounts(0)));
+        test_game
+            .make_move(MoveType::PLACE, Some(Cell::new(2, 2)))
+            .unwrap();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .block_index(11)
+            .build());
+        test_game.claim_timeout().unwrap();
+        assert!(test_game.game.is_finished);
+        assert_eq!(test_game.game.winner, Some(accounts(0)));
+        assert_eq!(test_game.game.finish_reason, Some(FinishReason::Timeout));
+    }
 
-        test_game.process_cell(Cell::new(0, 1));
-        assert_eq!(test_game.data, Board::new(5));
+    #[test]
+    fn test_claim_timeout_too_early() {
+        testing_env!(VMContextBuilder::new().block_index(0).build());
+        let mut test_game = GameWithData::new(0, accounts(0), accounts(1), 5, 5).unwrap();
+        test_game.game.rules.timeout = Some(10);
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(1))
+            .block_index(10)
+            .build());
+        assert_eq!(test_game.claim_timeout(), Err(HexError::NotTimedOut));
+    }
 
-        test_game.process_cell(Cell::new(0, 0));
-        assert_eq!(test_game.data, test_data);
+    #[test]
+    fn test_claim_timeout_by_player_to_move() {
+        testing_env!(VMContextBuilder::new().block_index(0).build());
+        let mut test_game = GameWithData::new(0, accounts(0), accounts(1), 5, 5).unwrap();
+        test_game.game.rules.timeout = Some(10);
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .block_index(100)
+            .build());
+        assert_eq!(
+            test_game.claim_timeout(),
+            Err(HexError::IncorrectPredecessor)
+        );
     }
 
     #[test]
-    fn test_make_move() {
-        let mut test_game = GameWithData::new(accounts(0), accounts(1), 5);
-        let mut test_data = Board::new(5);
-        assert_eq!(test_game.data, test_data);
+    fn test_make_move_records_moves() {
+        let mut test_game = GameWithData::new(0, accounts(0), accounts(1), 5, 5).unwrap();
 
         testing_env!(get_context(accounts(0)));
-        test_game.make_move(MoveType::PLACE, Some(Cell::new(3, 0)));
-        test_data.set_cell(&Cell::new(3, 0), 1);
-        assert_eq!(test_game.data, test_data);
-
+        test_game
+            .make_move(MoveType::PLACE, Some(Cell::new(3, 1)))
+            .unwrap();
         testing_env!(get_context(accounts(1)));
-        test_game.make_move(MoveType::SWAP, None);
-        test_data.set_cell(&Cell::new(3, 0), 0);
-        test_data.set_cell(&Cell::new(0, 3), 1);
-        assert_eq!(test_game.data, test_data);
-
+        test_game.make_move(MoveType::SWAP, None).unwrap();
         testing_env!(get_context(accounts(0)));
-        test_game.make_move(MoveType::PLACE, Some(Cell::new(4, 4)));
-        test_data.set_cell(&Cell::new(4, 4), 2);
-        assert_eq!(test_game.data, test_data);
+        test_game
+            .make_move(MoveType::PLACE, Some(Cell::new(0, 4)))
+            .unwrap();
+        test_game.make_move(MoveType::RESIGN, None).unwrap();
+
+        assert_eq!(
+            test_game.moves.to_vec(),
+            vec![8, SWAP_MOVE, 20, encode_resign(1)]
+        );
+    }
 
+    #[test]
+    fn test_make_move_incorrect_args() {
+        let mut test_game = GameWithData::new(0, accounts(0), accounts(1), 5, 5).unwrap();
+        assert_eq!(
+            test_game.make_move(MoveType::PLACE, None),
+            Err(HexError::IncorrectMoveArgs)
+        );
+    }
+
+    #[test]
+    fn test_make_move_wrong_player() {
+        let mut test_game = GameWithData::new(0, accounts(0), accounts(1), 5, 5).unwrap();
         testing_env!(get_context(accounts(1)));
-        test_game.make_move(MoveType::PLACE, Some(Cell::new(1, 2)));
-        test_data.set_cell(&Cell::new(1, 2), 1);
-        assert_eq!(test_game.data, test_data);
+        assert_eq!(
+            test_game.make_move(MoveType::PLACE, Some(Cell::new(0, 0))),
+            Err(HexError::IncorrectPredecessor)
+        );
+    }
 
-        testing_env!(get_context(accounts(0)));
-        test_game.make_move(MoveType::PLACE, Some(Cell::new(4, 2)));
-        assert_eq!(test_game.data, test_data);
+    fn play_moves(test_game: &mut GameWithData, cells: &[(usize, usize)]) {
+        for &(x, y) in cells {
+            let player = (test_game.game.state.turn % 2) as u8 + 1;
+            testing_env!(get_context(
+                test_game.game.get_player_account(player).unwrap()
+            ));
+            test_game
+                .make_move(MoveType::PLACE, Some(Cell::new(x, y)))
+                .unwrap();
+        }
+    }
 
-        testing_env!(get_context(accounts(1)));
-        test_game.make_move(MoveType::PLACE, Some(Cell::new(3, 2)));
-        assert_eq!(test_game.data, test_data);
+    #[test]
+    fn test_misere_single_cell() {
+        let mut test_game = GameWithData::new(0, accounts(0), accounts(1), 1, 1).unwrap();
+        test_game.game.rules.misere = true;
+
+        // The only move connects the first player's borders.
+        play_moves(&mut test_game, &[(0, 0)]);
+        assert!(test_game.game.is_finished);
+        assert_eq!(test_game.game.winner, Some(accounts(1)));
+        assert_eq!(test_game.game.finish_reason, Some(FinishReason::Connection));
+        assert_eq!(
+            test_game.game.state.winning_path,
+            Some(vec![Cell::new(0, 0)])
+        );
+    }
 
-        testing_env!(get_context(accounts(0)));
-        test_game.make_move(MoveType::PLACE, Some(Cell::new(4, 3)));
-        test_data.set_cell(&Cell::new(4, 2), 2);
-        test_data.set_cell(&Cell::new(4, 3), 2);
-        assert_eq!(test_game.data, test_data);
+    #[test]
+    fn test_misere_forced_connection() {
+        let mut test_game = GameWithData::new(0, accounts(0), accounts(1), 2, 2).unwrap();
+        test_game.game.rules.misere = true;
+
+        // The first player avoids a2, which would connect their borders, and
+        // leaves the same cell as the only move of the second player.
+        play_moves(&mut test_game, &[(0, 0), (1, 1), (1, 0)]);
+        assert!(!test_game.game.is_finished);
+        play_moves(&mut test_game, &[(0, 1)]);
+        assert!(test_game.game.is_finished);
+        assert_eq!(test_game.game.winner, Some(accounts(0)));
+        assert_eq!(
+            test_game.game.state.winning_path,
+            Some(vec![Cell::new(0, 1), Cell::new(1, 1)])
+        );
     }
 
     #[test]
-    #[should_panic]
-    fn test_make_move_incorrect_args() {
-        let mut test_game = GameWithData::new(accounts(0), accounts(1), 5);
-        test_game.make_move(MoveType::PLACE, None);
+    fn test_misere_own_connection_loses() {
+        let mut test_game = GameWithData::new(0, accounts(0), accounts(1), 2, 2).unwrap();
+        test_game.game.rules.misere = true;
+
+        play_moves(&mut test_game, &[(0, 0), (1, 1), (0, 1)]);
+        assert!(test_game.game.is_finished);
+        assert_eq!(test_game.game.winner, Some(accounts(1)));
     }
 
     #[test]
-    #[should_panic]
-    fn test_make_move_wrong_player() {
-        let mut test_game = GameWithData::new(accounts(0), accounts(1), 5);
-        testing_env!(get_context(accounts(1)));
-        test_game.make_move(MoveType::PLACE, Some(Cell::new(0, 0)));
+    fn test_misere_resign() {
+        let mut test_game = GameWithData::new(0, accounts(0), accounts(1), 3, 3).unwrap();
+        test_game.game.rules.misere = true;
+
+        play_moves(&mut test_game, &[(1, 1)]);
+        test_game.make_move(MoveType::RESIGN, None).unwrap();
+        assert_eq!(test_game.game.winner, Some(accounts(1)));
     }
 }
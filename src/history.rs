@@ -0,0 +1,234 @@
+use hex_core::{Board, Cell};
+use near_sdk::serde::{Deserialize, Serialize};
+
+use crate::error::HexError;
+use crate::game::Game;
+use crate::*;
+
+pub const SWAP_MOVE: u16 = u16::MAX;
+pub const FIRST_PLAYER_RESIGN: u16 = u16::MAX - 1;
+pub const SECOND_PLAYER_RESIGN: u16 = u16::MAX - 2;
+// Set on the code of the cell, boards have far fewer cells.
+pub const COLLISION_FLAG: u16 = 1 << 15;
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[cfg_attr(test, derive(PartialEq))]
+#[serde(crate = "near_sdk::serde")]
+pub struct Move {
+    pub move_type: MoveType,
+    pub cell: Option<Cell>,
+    pub player: u8,
+}
+
+pub fn encode_cell(cell: &Cell, width: usize) -> u16 {
+    (cell.y * width + cell.x) as u16
+}
+
+pub fn encode_resign(player: u8) -> u16 {
+    if player == 1 {
+        FIRST_PLAYER_RESIGN
+    } else {
+        SECOND_PLAYER_RESIGN
+    }
+}
+
+pub fn encode_collision(cell: &Cell, width: usize) -> u16 {
+    encode_cell(cell, width) | COLLISION_FLAG
+}
+
+pub fn decode_move(code: u16, ply: usize, width: usize) -> Move {
+    match code {
+        SWAP_MOVE => Move {
+            move_type: MoveType::SWAP,
+            cell: None,
+            player: 2,
+        },
+        FIRST_PLAYER_RESIGN | SECOND_PLAYER_RESIGN => Move {
+            move_type: MoveType::RESIGN,
+            cell: None,
+            player: if code == FIRST_PLAYER_RESIGN { 1 } else { 2 },
+        },
+        _ if code & COLLISION_FLAG != 0 => {
+            let index = (code & !COLLISION_FLAG) as usize;
+            Move {
+                move_type: MoveType::COLLISION,
+                cell: Some(Cell::new(index % width, index / width)),
+                player: (ply % 2) as u8 + 1,
+            }
+        }
+        _ => {
+            let index = code as usize;
+            Move {
+                move_type: MoveType::PLACE,
+                cell: Some(Cell::new(index % width, index / width)),
+                player: (ply % 2) as u8 + 1,
+            }
+        }
+    }
+}
+
+pub fn replay(mut game: Game, moves: &[u16]) -> Result<Board, HexError> {
+    let width = game.state.board.width;
+    for (ply, &code) in moves.iter().enumerate() {
+        let mv = decode_move(code, ply, width);
+        match mv.move_type {
+            MoveType::PLACE => game.place_counter(&mv.cell.unwrap(), mv.player)?,
+            MoveType::SWAP => {
+                game.swap_rule()?;
+            }
+            MoveType::RESIGN => {}
+            MoveType::COLLISION => game.lose_turn(),
+        }
+    }
+    Ok(game.state.board)
+}
+
+#[near_bindgen]
+impl Contract {
+    #[handle_result]
+    pub fn get_moves(
+        &self,
+        index: GameIndex,
+        from: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Vec<Move>, HexError> {
+        let game_with_data = self.games.get(index).ok_or(HexError::GameNotFound)?;
+        game_with_data.check_position_visible()?;
+        let width = game_with_data.game.state.board.width;
+        let moves = &game_with_data.moves;
+        let from = from.unwrap_or(0);
+        let to = std::cmp::min(from.saturating_add(limit.unwrap_or(50)), moves.len());
+        Ok((from..to)
+            .map(|ply| decode_move(moves.get(ply).unwrap(), ply as usize, width))
+            .collect())
+    }
+
+    #[handle_result]
+    pub fn get_position_at(&self, index: GameIndex, ply: u64) -> Result<Board, HexError> {
+        let game_with_data = self.games.get(index).ok_or(HexError::GameNotFound)?;
+        game_with_data.check_position_visible()?;
+        let moves = &game_with_data.moves;
+        if ply > moves.len() {
+            return Err(HexError::NotEnoughMoves);
+        }
+
+        let game = game_with_data.game;
+        let mut initial_game = Game::new(
+            game.first_player,
+            game.second_player,
+            game.state.board.width,
+            game.state.board.height,
+        )?;
+        initial_game.set_rules(game.rules)?;
+        let moves: Vec<u16> = (0..ply).map(|i| moves.get(i).unwrap()).collect();
+        replay(initial_game, &moves)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod history_tests {
+    use near_sdk::{
+        test_utils::{accounts, VMContextBuilder},
+        testing_env, AccountId, ONE_NEAR,
+    };
+
+    use super::{decode_move, encode_cell, encode_collision, encode_resign, Move, SWAP_MOVE};
+    use hex_core::{Board, Cell};
+
+    use crate::{error::HexError, test_utils::create_accepted_game, Contract, MoveType};
+
+    fn get_context(account: AccountId) -> near_sdk::VMContext {
+        VMContextBuilder::new()
+            .predecessor_account_id(account)
+            .attached_deposit(10 * ONE_NEAR)
+            .build()
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let code = encode_cell(&Cell::new(3, 4), 11);
+        assert_eq!(code, 47);
+        assert_eq!(
+            decode_move(code, 2, 11),
+            Move {
+                move_type: MoveType::PLACE,
+                cell: Some(Cell::new(3, 4)),
+                player: 1,
+            }
+        );
+        assert_eq!(decode_move(SWAP_MOVE, 1, 11).move_type, MoveType::SWAP);
+
+        let resign = decode_move(encode_resign(2), 6, 11);
+        assert_eq!(resign.move_type, MoveType::RESIGN);
+        assert_eq!(resign.player, 2);
+
+        assert_eq!(
+            decode_move(encode_collision(&Cell::new(31, 31), 32), 3, 32),
+            Move {
+                move_type: MoveType::COLLISION,
+                cell: Some(Cell::new(31, 31)),
+                player: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_get_moves_and_position() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        let id = create_accepted_game(&mut contract, accounts(0), accounts(1), Some(4), None, None);
+
+        testing_env!(get_context(accounts(0)));
+        contract
+            .make_move(id, MoveType::PLACE, Some(Cell::new(3, 0)))
+            .unwrap();
+        testing_env!(get_context(accounts(1)));
+        contract.make_move(id, MoveType::SWAP, None).unwrap();
+        testing_env!(get_context(accounts(0)));
+        contract
+            .make_move(id, MoveType::PLACE, Some(Cell::new(1, 2)))
+            .unwrap();
+        testing_env!(get_context(accounts(1)));
+        contract.make_move(id, MoveType::RESIGN, None).unwrap();
+
+        let moves = contract.get_moves(id, None, None).unwrap();
+        assert_eq!(moves.len(), 4);
+        assert_eq!(moves[0].cell, Some(Cell::new(3, 0)));
+        assert_eq!(moves[1].move_type, MoveType::SWAP);
+        assert_eq!(moves[2].cell, Some(Cell::new(1, 2)));
+        assert_eq!(moves[2].player, 1);
+        assert_eq!(moves[3].move_type, MoveType::RESIGN);
+        assert_eq!(moves[3].player, 2);
+        assert_eq!(contract.get_moves(id, Some(1), Some(2)).unwrap().len(), 2);
+
+        assert_eq!(
+            contract.get_position_at(id, 0).unwrap(),
+            Board::new(4, 4).unwrap()
+        );
+
+        let mut board = Board::new(4, 4).unwrap();
+        board.set_cell(&Cell::new(3, 0), 1).unwrap();
+        assert_eq!(contract.get_position_at(id, 1).unwrap(), board);
+
+        board.set_cell(&Cell::new(3, 0), 0).unwrap();
+        board.set_cell(&Cell::new(0, 3), 2).unwrap();
+        assert_eq!(contract.get_position_at(id, 2).unwrap(), board);
+
+        board.set_cell(&Cell::new(1, 2), 1).unwrap();
+        assert_eq!(contract.get_position_at(id, 3).unwrap(), board);
+        assert_eq!(contract.get_position_at(id, 4).unwrap(), board);
+        assert_eq!(contract.get_game(id).unwrap().state.board, board);
+    }
+
+    #[test]
+    fn test_get_position_too_far() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        let id = create_accepted_game(&mut contract, accounts(0), accounts(1), Some(4), None, None);
+        assert_eq!(
+            contract.get_position_at(id, 1),
+            Err(HexError::NotEnoughMoves)
+        );
+    }
+}
@@ -1,25 +1,52 @@
-use cell::Cell;
+use challenge::{Challenge, ChallengeIndex};
+use error::HexError;
+use events::HexGameEvent;
 use external::{Stream, StreamStatus};
-use game::{Game, GameIndex};
+use game::{Game, GameIndex, Rules};
 use game_with_data::GameWithData;
-use near_contract_standards::non_fungible_token::refund_deposit;
+use hex_core::Cell;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::Vector;
+use near_sdk::collections::{LookupMap, TreeMap, UnorderedMap, UnorderedSet, Vector};
+use near_sdk::json_types::U128;
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{env, near_bindgen, require, AccountId, BorshStorageKey, PanicOnDefault, Promise};
+use near_sdk::{
+    env, near_bindgen, AccountId, Balance, BorshStorageKey, CryptoHash, PanicOnDefault, Promise,
+};
+use rating::{LeaderboardKey, Rating};
 use roketo::get_account_outgoing_streams;
+use wager::{pay_out, refund_deposit_with_stake, refund_stake};
 
 #[derive(BorshSerialize, BorshStorageKey)]
 pub enum StorageKey {
     Games,
-    Field { game_id: GameIndex },
+    Moves { game_id: GameIndex },
+    AcceptedTokens,
+    UnclaimedStakes,
+    Challenges,
+    PlayerGames,
+    PlayerGamesPerAccount { account_hash: CryptoHash },
+    ActivePlayerGames,
+    ActivePlayerGamesPerAccount { account_hash: CryptoHash },
+    Ratings,
+    RatingsPerSize { field_size: u64 },
+    StorageDeposits,
+    PendingPlayerGames,
+    PendingPlayerGamesPerAccount { account_hash: CryptoHash },
+    FinishedPlayerGames,
+    FinishedPlayerGamesPerAccount { account_hash: CryptoHash },
+    Leaderboards,
+    LeaderboardPerSize { field_size: u64 },
 }
 
-#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
 #[serde(crate = "near_sdk::serde")]
 pub enum MoveType {
     PLACE,
     SWAP,
+    RESIGN,
+    // Dark Hex move into a hidden counter of the opponent, only in the history.
+    COLLISION,
 }
 
 #[near_bindgen]
@@ -27,6 +54,17 @@ pub enum MoveType {
 pub struct Contract {
     pub games: Vector<GameWithData>,
     pub roketo_acc: Option<AccountId>,
+    pub accepted_tokens: UnorderedSet<AccountId>,
+    pub unclaimed_stakes: LookupMap<(AccountId, AccountId), Balance>,
+    pub challenges: UnorderedMap<ChallengeIndex, Challenge>,
+    pub next_challenge_index: ChallengeIndex,
+    pub player_games: LookupMap<AccountId, Vector<GameIndex>>,
+    pub pending_player_games: LookupMap<AccountId, UnorderedSet<GameIndex>>,
+    pub active_player_games: LookupMap<AccountId, UnorderedSet<GameIndex>>,
+    pub finished_player_games: LookupMap<AccountId, Vector<GameIndex>>,
+    pub ratings: LookupMap<usize, UnorderedMap<AccountId, Rating>>,
+    pub leaderboards: LookupMap<usize, TreeMap<LeaderboardKey, Rating>>,
+    pub storage_deposits: LookupMap<AccountId, Balance>,
 }
 
 #[near_bindgen]
@@ -36,76 +74,181 @@ impl Contract {
         Self {
             games: Vector::new(StorageKey::Games),
             roketo_acc,
+            accepted_tokens: UnorderedSet::new(StorageKey::AcceptedTokens),
+            unclaimed_stakes: LookupMap::new(StorageKey::UnclaimedStakes),
+            challenges: UnorderedMap::new(StorageKey::Challenges),
+            next_challenge_index: 0,
+            player_games: LookupMap::new(StorageKey::PlayerGames),
+            pending_player_games: LookupMap::new(StorageKey::PendingPlayerGames),
+            active_player_games: LookupMap::new(StorageKey::ActivePlayerGames),
+            finished_player_games: LookupMap::new(StorageKey::FinishedPlayerGames),
+            ratings: LookupMap::new(StorageKey::Ratings),
+            leaderboards: LookupMap::new(StorageKey::Leaderboards),
+            storage_deposits: LookupMap::new(StorageKey::StorageDeposits),
         }
     }
 
     #[payable]
+    #[handle_result]
     pub fn create_game(
         &mut self,
         first_player: AccountId,
         second_player: AccountId,
         field_size: Option<usize>,
-    ) -> GameIndex {
+        field_height: Option<usize>,
+        rules: Option<Rules>,
+        stake: Option<U128>,
+    ) -> Result<GameIndex, HexError> {
         let initial_storage_usage = env::storage_usage();
 
-        let index = self.games.len();
-        let size = field_size.unwrap_or(11);
-        self.games
-            .push(&GameWithData::new(first_player, second_player, size));
+        let width = field_size.unwrap_or(11);
+        let height = field_height.unwrap_or(width);
+        let stake = stake.map(|x| x.0).unwrap_or(0);
+        let mut game_with_data =
+            GameWithData::new(self.games.len(), first_player, second_player, width, height)?;
+        game_with_data.game.set_rules(rules.unwrap_or_default())?;
+        game_with_data
+            .game
+            .set_stake(&env::predecessor_account_id(), stake)?;
+        let index = self.internal_add_game(&game_with_data);
 
         let required_storage_in_bytes = env::storage_usage() - initial_storage_usage;
-        refund_deposit(required_storage_in_bytes);
-
-        env::log_str("Created board:");
-        self.games.get(index).unwrap().game.board.debug_logs();
-        index
+        refund_deposit_with_stake(required_storage_in_bytes, stake)?;
+        Ok(index)
     }
 
     pub fn get_game(&self, index: GameIndex) -> Option<Game> {
-        let game = self.games.get(index).map(|x| x.game);
-        if game.is_some() {
-            env::log_str("Game board:");
-            game.clone().unwrap().board.debug_logs();
+        self.games.get(index).map(|x| x.game)
+    }
+
+    #[handle_result]
+    pub fn get_winning_path(&self, index: GameIndex) -> Result<Option<Vec<Cell>>, HexError> {
+        self.games
+            .get(index)
+            .map(|x| x.game.state.winning_path)
+            .ok_or(HexError::GameNotFound)
+    }
+
+    #[payable]
+    #[handle_result]
+    pub fn accept_game(&mut self, index: GameIndex) -> Result<Game, HexError> {
+        let mut game_with_data = self.games.get(index).ok_or(HexError::GameNotFound)?;
+        if game_with_data.game.is_finished {
+            return Err(HexError::GameFinished);
+        }
+        if game_with_data.game.stake_token.is_some() {
+            return Err(HexError::StakeInTokens);
         }
-        game
+        let account = env::predecessor_account_id();
+        game_with_data.game.accept(&account)?;
+        refund_deposit_with_stake(0, game_with_data.game.stake.0)?;
+        self.internal_on_game_status_changed(index, &game_with_data.game);
+        HexGameEvent::GameAccepted {
+            game_id: index,
+            account_id: &account,
+        }
+        .emit();
+
+        self.games.replace(index, &game_with_data);
+        Ok(game_with_data.game)
     }
 
-    pub fn make_move(&mut self, index: GameIndex, move_type: MoveType, cell: Option<Cell>) -> Game {
-        let mut game_with_data = self.games.get(index).expect("Game doesn't exist.");
-        require!(
-            !game_with_data.game.is_finished,
-            "Game is already finished!"
-        );
+    #[handle_result]
+    pub fn cancel_game(&mut self, index: GameIndex) -> Result<Game, HexError> {
+        let mut game_with_data = self.games.get(index).ok_or(HexError::GameNotFound)?;
+        let account = env::predecessor_account_id();
+        if account != game_with_data.game.first_player
+            && account != game_with_data.game.second_player
+        {
+            return Err(HexError::IncorrectPredecessor);
+        }
 
-        let old_board = game_with_data.game.board.clone();
-        game_with_data.make_move(move_type, cell);
+        let creator = if game_with_data.game.pending_acceptance.as_ref()
+            == Some(&game_with_data.game.first_player)
+        {
+            game_with_data.game.second_player.clone()
+        } else {
+            game_with_data.game.first_player.clone()
+        };
+        game_with_data.game.cancel()?;
+        refund_stake(creator, &game_with_data.game);
+        self.internal_on_game_finished(index, &game_with_data.game);
 
-        env::log_str("Old board:");
-        old_board.debug_logs();
+        self.games.replace(index, &game_with_data);
+        Ok(game_with_data.game)
+    }
 
-        env::log_str("New board:");
-        game_with_data.game.board.debug_logs();
+    #[handle_result]
+    pub fn make_move(
+        &mut self,
+        index: GameIndex,
+        move_type: MoveType,
+        cell: Option<Cell>,
+    ) -> Result<Game, HexError> {
+        let mut game_with_data = self.internal_get_active_game(index)?;
 
-        if game_with_data.game.is_finished {
-            if game_with_data.game.turn % 2 == 1 {
-                env::log_str("First player wins!");
+        let is_swap = move_type == MoveType::SWAP;
+        if let Some(cell) = game_with_data.make_move(move_type, cell)? {
+            let player = env::predecessor_account_id();
+            let notation = cell.to_notation();
+            let ply = game_with_data.moves.len() - 1;
+            if is_swap {
+                HexGameEvent::Swap {
+                    game_id: index,
+                    player: &player,
+                    cell: &cell,
+                    notation,
+                    ply,
+                }
+                .emit();
             } else {
-                env::log_str("Second player wins!");
+                HexGameEvent::MoveMade {
+                    game_id: index,
+                    player: &player,
+                    cell: &cell,
+                    notation,
+                    ply,
+                }
+                .emit();
+            }
+        }
+
+        if game_with_data.game.is_finished {
+            self.internal_on_game_finished(index, &game_with_data.game);
+        }
+
+        self.games.replace(index, &game_with_data);
+        Ok(self.games.get(index).unwrap().game)
+    }
+
+    #[handle_result]
+    pub fn make_move_notation(&mut self, index: GameIndex, mv: String) -> Result<Game, HexError> {
+        match mv.trim().to_ascii_lowercase().as_str() {
+            "swap" => self.make_move(index, MoveType::SWAP, None),
+            "resign" => self.make_move(index, MoveType::RESIGN, None),
+            notation => {
+                let cell = Cell::from_notation(notation).ok_or(HexError::IncorrectNotation)?;
+                self.make_move(index, MoveType::PLACE, Some(cell))
             }
         }
+    }
+
+    #[handle_result]
+    pub fn claim_timeout(&mut self, index: GameIndex) -> Result<Game, HexError> {
+        let mut game_with_data = self.internal_get_active_game(index)?;
+
+        game_with_data.claim_timeout()?;
+        self.internal_on_game_finished(index, &game_with_data.game);
 
         self.games.replace(index, &game_with_data);
-        return self.games.get(index).unwrap().game;
+        Ok(game_with_data.game)
     }
 
-    pub fn check_premium_account(&self, account_id: AccountId) -> Promise {
-        get_account_outgoing_streams(
-            account_id,
-            self.roketo_acc
-                .clone()
-                .expect("No Roketo account to check premium."),
-        )
-        .then(Self::ext(env::current_account_id()).check_premium_account_internal())
+    #[handle_result]
+    pub fn check_premium_account(&self, account_id: AccountId) -> Result<Promise, HexError> {
+        let roketo_acc = self.roketo_acc.clone().ok_or(HexError::NoRoketoAccount)?;
+        Ok(get_account_outgoing_streams(account_id, roketo_acc)
+            .then(Self::ext(env::current_account_id()).check_premium_account_internal()))
     }
 
     #[private]
@@ -120,23 +263,133 @@ impl Contract {
     }
 }
 
-pub mod board;
-pub mod cell;
+impl Contract {
+    fn internal_add_game(&mut self, game_with_data: &GameWithData) -> GameIndex {
+        let index = self.games.len();
+        self.games.push(game_with_data);
+
+        let game = &game_with_data.game;
+        HexGameEvent::game_created(index, game).emit();
+        self.internal_add_player_game(&game.first_player, index, game.status());
+        if game.second_player != game.first_player {
+            self.internal_add_player_game(&game.second_player, index, game.status());
+        }
+        index
+    }
+
+    fn internal_on_game_status_changed(&mut self, index: GameIndex, game: &Game) {
+        self.internal_update_player_game(&game.first_player, index, game.status());
+        if game.second_player != game.first_player {
+            self.internal_update_player_game(&game.second_player, index, game.status());
+        }
+    }
+
+    fn internal_get_active_game(&self, index: GameIndex) -> Result<GameWithData, HexError> {
+        let game_with_data = self.games.get(index).ok_or(HexError::GameNotFound)?;
+        if game_with_data.game.is_finished {
+            return Err(HexError::GameFinished);
+        }
+        if game_with_data.game.pending_acceptance.is_some() {
+            return Err(HexError::GameNotAccepted);
+        }
+        Ok(game_with_data)
+    }
+
+    fn internal_on_game_finished(&mut self, index: GameIndex, game: &Game) {
+        HexGameEvent::GameFinished {
+            game_id: index,
+            winner: game.winner.as_ref(),
+            reason: game.finish_reason.as_ref().unwrap(),
+        }
+        .emit();
+        pay_out(game);
+        self.internal_update_ratings(game);
+        self.internal_on_game_status_changed(index, game);
+    }
+}
+
+pub mod analysis;
+pub mod challenge;
+pub mod clock;
+pub mod dark;
+pub mod error;
+pub mod events;
 pub mod external;
 pub mod game;
 pub mod game_with_data;
+pub mod history;
+pub mod player_games;
+pub mod rating;
 pub mod roketo;
+pub mod sgf;
+pub mod wager;
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+pub(crate) mod test_utils {
+    use near_sdk::{env, test_utils::VMContextBuilder, testing_env, AccountId, ONE_NEAR};
+
+    use crate::{
+        game::{GameIndex, Rules},
+        Contract,
+    };
+
+    // Creates the game on behalf of the first player and accepts it by the
+    // second one in the block of the current context.
+    pub(crate) fn create_accepted_game(
+        contract: &mut Contract,
+        first_player: AccountId,
+        second_player: AccountId,
+        field_size: Option<usize>,
+        field_height: Option<usize>,
+        rules: Option<Rules>,
+    ) -> GameIndex {
+        let (block_index, block_timestamp) = (env::block_height(), env::block_timestamp());
+        let context = |account: AccountId| {
+            VMContextBuilder::new()
+                .predecessor_account_id(account)
+                .attached_deposit(10 * ONE_NEAR)
+                .block_index(block_index)
+                .block_timestamp(block_timestamp)
+                .build()
+        };
+        testing_env!(context(first_player.clone()));
+        let index = contract
+            .create_game(
+                first_player.clone(),
+                second_player.clone(),
+                field_size,
+                field_height,
+                rules,
+                None,
+            )
+            .unwrap();
+        if second_player != first_player {
+            testing_env!(context(second_player.clone()));
+            contract.accept_game(index).unwrap();
+        }
+        index
+    }
+}
 
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod contract_tests {
     use core::fmt::Debug;
     use near_sdk::{
-        test_utils::{accounts, VMContextBuilder},
-        testing_env, AccountId, ONE_NEAR,
+        env,
+        json_types::U128,
+        mock::VmAction,
+        test_utils::{accounts, get_created_receipts, VMContextBuilder},
+        testing_env, AccountId, Gas, ONE_NEAR,
     };
 
+    use hex_core::{Board, Cell, MAX_FIELD_SIZE};
+
     use crate::{
-        board::Board, cell::Cell, game::Game, game_with_data::GameWithData, Contract, MoveType,
+        error::HexError,
+        game::{FinishReason, Game, Rules},
+        game_with_data::GameWithData,
+        test_utils::create_accepted_game,
+        Contract, MoveType,
     };
 
     fn get_context(account: AccountId) -> near_sdk::VMContext {
@@ -150,17 +403,24 @@ mod contract_tests {
         fn eq(&self, other: &Self) -> bool {
             self.first_player == other.first_player
                 && self.second_player == other.second_player
-                && self.turn == other.turn
-                && self.board == other.board
+                && self.state == other.state
                 && self.current_block_height == other.current_block_height
                 && self.prev_block_height == other.prev_block_height
+                && self.rules == other.rules
+                && self.clocks == other.clocks
+                && self.last_move_timestamp == other.last_move_timestamp
+                && self.stake == other.stake
+                && self.stake_token == other.stake_token
+                && self.pending_acceptance == other.pending_acceptance
                 && self.is_finished == other.is_finished
+                && self.winner == other.winner
+                && self.finish_reason == other.finish_reason
         }
     }
 
     impl PartialEq for GameWithData {
         fn eq(&self, other: &Self) -> bool {
-            self.game == other.game && self.data == other.data
+            self.game == other.game && self.moves.to_vec() == other.moves.to_vec()
         }
     }
 
@@ -169,11 +429,18 @@ mod contract_tests {
             f.debug_struct("Game")
                 .field("first_player", &self.first_player)
                 .field("second_player", &self.second_player)
-                .field("turn", &self.turn)
-                .field("board", &self.board)
+                .field("state", &self.state)
                 .field("current_block_height", &self.current_block_height)
                 .field("prev_block_height", &self.prev_block_height)
+                .field("rules", &self.rules)
+                .field("clocks", &self.clocks)
+                .field("last_move_timestamp", &self.last_move_timestamp)
+                .field("stake", &self.stake)
+                .field("stake_token", &self.stake_token)
+                .field("pending_acceptance", &self.pending_acceptance)
                 .field("is_finished", &self.is_finished)
+                .field("winner", &self.winner)
+                .field("finish_reason", &self.finish_reason)
                 .finish()
         }
     }
@@ -182,7 +449,7 @@ mod contract_tests {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             f.debug_struct("GameWithData")
                 .field("game", &self.game)
-                .field("data", &self.data)
+                .field("moves", &self.moves.to_vec())
                 .finish()
         }
     }
@@ -191,9 +458,31 @@ mod contract_tests {
     fn test_create_get() {
         testing_env!(get_context(accounts(2)));
         let mut contract = Contract::new(None);
-        contract.create_game(accounts(1), accounts(2), Some(3));
-        contract.create_game(accounts(4), accounts(3), Some(4));
-        let id = contract.create_game(accounts(0), accounts(1), None);
+        contract
+            .create_game(accounts(1), accounts(2), Some(3), None, None, None)
+            .unwrap();
+        // A game against yourself doesn't wait for anyone.
+        let id = contract
+            .create_game(accounts(2), accounts(2), Some(4), None, None, None)
+            .unwrap();
+        assert_eq!(contract.get_game(id).unwrap().pending_acceptance, None);
+        assert_eq!(
+            contract.create_game(accounts(0), accounts(1), None, None, None, None),
+            Err(HexError::NotAPlayer)
+        );
+        assert_eq!(
+            contract.create_game(accounts(2), accounts(2), Some(0), None, None, None),
+            Err(HexError::EmptyField)
+        );
+        assert_eq!(
+            contract.create_game(accounts(2), accounts(2), Some(5), Some(0), None, None),
+            Err(HexError::EmptyField)
+        );
+
+        testing_env!(get_context(accounts(0)));
+        let id = contract
+            .create_game(accounts(0), accounts(1), None, None, None, None)
+            .unwrap();
         assert_eq!(id, 2);
         let game = contract.get_game(id);
 
@@ -201,28 +490,388 @@ mod contract_tests {
         assert!(game.is_some());
         assert_eq!(game.clone().unwrap().first_player, accounts(0));
         assert_eq!(game.clone().unwrap().second_player, accounts(1));
-        assert_eq!(game.unwrap().board, Board::new(11));
+        assert_eq!(game.clone().unwrap().pending_acceptance, Some(accounts(1)));
+        assert_eq!(game.unwrap().state.board, Board::new(11, 11).unwrap());
+    }
+
+    #[test]
+    fn test_game_json() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        let id = create_accepted_game(&mut contract, accounts(0), accounts(1), Some(2), None, None);
+        testing_env!(get_context(accounts(0)));
+        contract
+            .make_move(id, MoveType::PLACE, Some(Cell::new(1, 0)))
+            .unwrap();
+
+        let json = near_sdk::serde_json::to_value(contract.get_game(id).unwrap()).unwrap();
+        assert_eq!(json["turn"], 1);
+        assert_eq!(
+            json["board"],
+            near_sdk::serde_json::json!({"width": 2, "height": 2, "field": "BA=="})
+        );
+        assert!(json.get("state").is_none());
+        assert!(json.get("connectivity").is_none());
+    }
+
+    #[test]
+    fn test_create_game_with_handicap() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        let rules: Rules = near_sdk::serde_json::from_str(
+            r#"{"timeout": null, "opening": "NoSwap", "handicap": {"player": 2, "cells": [{"x": 1, "y": 1}, {"x": 2, "y": 0}]}}"#,
+        )
+        .unwrap();
+        let id = create_accepted_game(
+            &mut contract,
+            accounts(0),
+            accounts(1),
+            Some(3),
+            None,
+            Some(rules),
+        );
+
+        let mut board = Board::new(3, 3).unwrap();
+        board.set_cell(&Cell::new(1, 1), 2).unwrap();
+        board.set_cell(&Cell::new(2, 0), 2).unwrap();
+        assert_eq!(contract.get_game(id).unwrap().state.board, board);
+        assert_eq!(contract.get_position_at(id, 0).unwrap(), board);
+
+        testing_env!(get_context(accounts(0)));
+        contract.make_move_notation(id, "a1".to_string()).unwrap();
+        testing_env!(get_context(accounts(1)));
+        assert_eq!(
+            contract.make_move_notation(id, "swap".to_string()),
+            Err(HexError::SwapNotAllowed)
+        );
+        contract.make_move_notation(id, "a2".to_string()).unwrap();
+        assert!(contract.get_game(id).unwrap().is_finished);
+        assert_eq!(
+            contract.export_sgf(id).unwrap(),
+            "(;FF[4]GM[11]SZ[3]PB[alice]PW[bob]RE[W+]AW[b2][c1];B[a1];W[a2])"
+        );
+    }
+
+    #[test]
+    fn test_create_misere_game() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        // Rules without the variant are still accepted.
+        let rules: Rules = near_sdk::serde_json::from_str(r#"{"timeout": 5}"#).unwrap();
+        assert!(!rules.misere);
+        let rules = Rules {
+            misere: true,
+            ..rules
+        };
+        let id = create_accepted_game(
+            &mut contract,
+            accounts(0),
+            accounts(1),
+            Some(2),
+            None,
+            Some(rules),
+        );
+        let json = near_sdk::serde_json::to_value(contract.get_game(id).unwrap()).unwrap();
+        assert_eq!(
+            json["rules"],
+            near_sdk::serde_json::json!({"timeout": 5, "misere": true, "dark": false, "opening": "Swap", "handicap": null, "clock": null})
+        );
+
+        testing_env!(get_context(accounts(0)));
+        contract.make_move_notation(id, "a1".to_string()).unwrap();
+        testing_env!(get_context(accounts(1)));
+        contract.make_move_notation(id, "b1".to_string()).unwrap();
+        testing_env!(get_context(accounts(0)));
+        let game = contract.make_move_notation(id, "a2".to_string()).unwrap();
+        assert_eq!(game.winner, Some(accounts(1)));
+        assert_eq!(game.finish_reason, Some(FinishReason::Connection));
     }
 
     #[test]
     fn test_make_move() {
         testing_env!(get_context(accounts(2)));
         let mut contract = Contract::new(None);
-        let id = contract.create_game(accounts(0), accounts(1), Some(5));
+        let id = create_accepted_game(&mut contract, accounts(0), accounts(1), Some(5), None, None);
 
         testing_env!(get_context(accounts(0)));
-        let mut test_game = GameWithData::new(accounts(0), accounts(1), 5);
+        let mut test_game = GameWithData::new(id + 1, accounts(0), accounts(1), 5, 5).unwrap();
         assert_eq!(test_game, contract.games.get(id).unwrap());
 
-        let game = contract.make_move(id, MoveType::PLACE, Some(Cell::new(4, 0)));
-        test_game.make_move(MoveType::PLACE, Some(Cell::new(4, 0)));
+        let game = contract
+            .make_move(id, MoveType::PLACE, Some(Cell::new(4, 0)))
+            .unwrap();
+        test_game
+            .make_move(MoveType::PLACE, Some(Cell::new(4, 0)))
+            .unwrap();
         assert_eq!(test_game.game, game);
         assert_eq!(test_game, contract.games.get(id).unwrap());
 
         testing_env!(get_context(accounts(1)));
-        let game = contract.make_move(id, MoveType::SWAP, Some(Cell::new(4, 0)));
-        test_game.make_move(MoveType::SWAP, Some(Cell::new(4, 0)));
+        let game = contract
+            .make_move(id, MoveType::SWAP, Some(Cell::new(4, 0)))
+            .unwrap();
+        test_game
+            .make_move(MoveType::SWAP, Some(Cell::new(4, 0)))
+            .unwrap();
         assert_eq!(test_game.game, game);
         assert_eq!(test_game, contract.games.get(id).unwrap());
     }
+
+    #[test]
+    fn test_get_winning_path() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        let id = create_accepted_game(&mut contract, accounts(0), accounts(1), Some(3), None, None);
+
+        let moves = [(1, 0), (0, 0), (1, 1), (2, 0), (0, 2)];
+        for (i, &(x, y)) in moves.iter().enumerate() {
+            assert_eq!(contract.get_winning_path(id).unwrap(), None);
+            testing_env!(get_context(accounts(i % 2)));
+            contract
+                .make_move(id, MoveType::PLACE, Some(Cell::new(x, y)))
+                .unwrap();
+        }
+        assert_eq!(
+            contract.get_winning_path(id).unwrap(),
+            Some(vec![Cell::new(1, 0), Cell::new(1, 1), Cell::new(0, 2)])
+        );
+    }
+
+    #[test]
+    fn test_resign() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        let id = create_accepted_game(&mut contract, accounts(0), accounts(1), Some(5), None, None);
+
+        testing_env!(get_context(accounts(0)));
+        contract
+            .make_move(id, MoveType::PLACE, Some(Cell::new(1, 3)))
+            .unwrap();
+
+        testing_env!(get_context(accounts(1)));
+        let game = contract.make_move(id, MoveType::RESIGN, None).unwrap();
+        assert!(game.is_finished);
+        assert_eq!(game.winner, Some(accounts(0)));
+        assert_eq!(game.finish_reason, Some(FinishReason::Resignation));
+    }
+
+    #[test]
+    fn test_make_move_notation() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        let id = create_accepted_game(&mut contract, accounts(0), accounts(1), Some(5), None, None);
+
+        testing_env!(get_context(accounts(0)));
+        let game = contract.make_move_notation(id, "b4".to_string()).unwrap();
+        assert_eq!(game.state.board.get_cell(&Cell::new(1, 3)).unwrap(), 1);
+
+        testing_env!(get_context(accounts(1)));
+        let game = contract.make_move_notation(id, "Swap".to_string()).unwrap();
+        assert_eq!(game.state.board.get_cell(&Cell::new(3, 1)).unwrap(), 2);
+
+        testing_env!(get_context(accounts(0)));
+        contract.make_move_notation(id, "e5".to_string()).unwrap();
+        testing_env!(get_context(accounts(1)));
+        let game = contract
+            .make_move_notation(id, "resign".to_string())
+            .unwrap();
+        assert_eq!(game.winner, Some(accounts(0)));
+        assert_eq!(contract.get_moves(id, None, None).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_make_move_incorrect_notation() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        let id = create_accepted_game(&mut contract, accounts(0), accounts(1), Some(5), None, None);
+
+        testing_env!(get_context(accounts(0)));
+        assert_eq!(
+            contract.make_move_notation(id, "pass".to_string()),
+            Err(HexError::IncorrectNotation)
+        );
+    }
+
+    #[test]
+    fn test_make_move_notation_out_of_bounds() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        let id = create_accepted_game(&mut contract, accounts(0), accounts(1), Some(5), None, None);
+
+        testing_env!(get_context(accounts(0)));
+        assert_eq!(
+            contract.make_move_notation(id, "f1".to_string()),
+            Err(HexError::CellOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_claim_timeout() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        let rules = Rules {
+            timeout: Some(20),
+            ..Rules::default()
+        };
+        let id = create_accepted_game(
+            &mut contract,
+            accounts(0),
+            accounts(1),
+            Some(5),
+            None,
+            Some(rules),
+        );
+        assert_eq!(contract.get_game(id).unwrap().rules.timeout, Some(20));
+
+        testing_env!(get_context(accounts(0)));
+        contract
+            .make_move(id, MoveType::PLACE, Some(Cell::new(1, 3)))
+            .unwrap();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .block_index(21)
+            .build());
+        let game = contract.claim_timeout(id).unwrap();
+        assert!(game.is_finished);
+        assert_eq!(game.winner, Some(accounts(0)));
+        assert_eq!(game.finish_reason, Some(FinishReason::Timeout));
+    }
+
+    #[test]
+    fn test_wager_payout() {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        let id = contract
+            .create_game(
+                accounts(0),
+                accounts(1),
+                Some(2),
+                None,
+                None,
+                Some(U128(ONE_NEAR)),
+            )
+            .unwrap();
+        let game = contract.get_game(id).unwrap();
+        assert_eq!(game.stake, U128(ONE_NEAR));
+        assert_eq!(game.pending_acceptance, Some(accounts(1)));
+
+        testing_env!(get_context(accounts(1)));
+        let game = contract.accept_game(id).unwrap();
+        assert_eq!(game.pending_acceptance, None);
+
+        testing_env!(get_context(accounts(0)));
+        contract
+            .make_move(id, MoveType::PLACE, Some(Cell::new(1, 0)))
+            .unwrap();
+
+        testing_env!(get_context(accounts(1)));
+        contract.make_move(id, MoveType::SWAP, None).unwrap();
+
+        testing_env!(get_context(accounts(0)));
+        contract.make_move(id, MoveType::RESIGN, None).unwrap();
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id, accounts(1));
+        assert_eq!(
+            receipts[0].actions,
+            vec![VmAction::Transfer {
+                deposit: 2 * ONE_NEAR
+            }]
+        );
+    }
+
+    #[test]
+    fn test_make_move_before_accept() {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        let id = contract
+            .create_game(
+                accounts(0),
+                accounts(1),
+                Some(5),
+                None,
+                None,
+                Some(U128(ONE_NEAR)),
+            )
+            .unwrap();
+        assert_eq!(
+            contract.make_move(id, MoveType::PLACE, Some(Cell::new(1, 3))),
+            Err(HexError::GameNotAccepted)
+        );
+    }
+
+    #[test]
+    fn test_cancel_game() {
+        testing_env!(get_context(accounts(1)));
+        let mut contract = Contract::new(None);
+        let id = contract
+            .create_game(
+                accounts(0),
+                accounts(1),
+                Some(5),
+                None,
+                None,
+                Some(U128(ONE_NEAR)),
+            )
+            .unwrap();
+
+        testing_env!(get_context(accounts(0)));
+        let game = contract.cancel_game(id).unwrap();
+        assert!(game.is_finished);
+        assert_eq!(game.winner, None);
+        assert_eq!(game.finish_reason, Some(FinishReason::Cancellation));
+
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id, accounts(1));
+        assert_eq!(
+            receipts[0].actions,
+            vec![VmAction::Transfer { deposit: ONE_NEAR }]
+        );
+    }
+
+    // The mocked runtime charges only for host functions, mostly the storage
+    // of the game with its connectivity, so every move costs about the same
+    // regardless of the size of the groups it joins.
+    #[test]
+    fn test_move_gas() {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        let size = MAX_FIELD_SIZE;
+        let id = create_accepted_game(
+            &mut contract,
+            accounts(0),
+            accounts(1),
+            Some(size),
+            None,
+            None,
+        );
+
+        let mut cells: Vec<Cell> = (0..size * size)
+            .map(|i| Cell::new(i % size, i / size))
+            .collect();
+        let mut seed: u64 = 7;
+        for i in (1..cells.len()).rev() {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            cells.swap(i, ((seed >> 33) % (i as u64 + 1)) as usize);
+        }
+        let mut gas = vec![];
+        for (ply, cell) in cells.into_iter().enumerate() {
+            testing_env!(get_context(accounts(ply % 2)));
+            let game = contract.make_move(id, MoveType::PLACE, Some(cell)).unwrap();
+            if game.is_finished {
+                // Finishing the game also updates the ratings and the indices.
+                assert!(env::used_gas() < Gas::ONE_TERA * 5);
+                break;
+            }
+            gas.push(env::used_gas().0);
+        }
+        assert!(gas.len() > 100);
+        let (min, max) = (*gas.iter().min().unwrap(), *gas.iter().max().unwrap());
+        assert!(max < Gas::ONE_TERA.0);
+        assert!(max - min < min / 100);
+    }
 }
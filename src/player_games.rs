@@ -0,0 +1,226 @@
+use near_sdk::collections::{UnorderedSet, Vector};
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::game::GameStatus;
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_games_for_player(
+        &self,
+        account_id: AccountId,
+        from: Option<u64>,
+        limit: Option<u64>,
+        status_filter: Option<GameStatus>,
+    ) -> Vec<(GameIndex, Game)> {
+        // Only the requested page of the index is loaded.
+        let from = from.unwrap_or(0);
+        let page = |indices: &Vector<GameIndex>| -> Vec<GameIndex> {
+            let to = std::cmp::min(from.saturating_add(limit.unwrap_or(50)), indices.len());
+            (from..to).map(|i| indices.get(i).unwrap()).collect()
+        };
+        let indices = match status_filter {
+            None => self.player_games.get(&account_id).map(|games| page(&games)),
+            Some(GameStatus::Pending) => self
+                .pending_player_games
+                .get(&account_id)
+                .map(|games| page(games.as_vector())),
+            Some(GameStatus::Active) => self
+                .active_player_games
+                .get(&account_id)
+                .map(|games| page(games.as_vector())),
+            Some(GameStatus::Finished) => self
+                .finished_player_games
+                .get(&account_id)
+                .map(|games| page(&games)),
+        };
+        indices
+            .unwrap_or_default()
+            .into_iter()
+            .map(|index| (index, self.games.get(index).unwrap().game))
+            .collect()
+    }
+
+    pub fn get_games_awaiting_move(&self, account_id: AccountId) -> Vec<(GameIndex, Game)> {
+        let indices = match self.active_player_games.get(&account_id) {
+            Some(indices) => indices,
+            None => return vec![],
+        };
+        indices
+            .iter()
+            .map(|index| (index, self.games.get(index).unwrap().game))
+            .filter(|(_, game)| game.get_current_player() == account_id)
+            .collect()
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_add_player_game(
+        &mut self,
+        account_id: &AccountId,
+        index: GameIndex,
+        status: GameStatus,
+    ) {
+        let account_hash = env::sha256_array(account_id.as_bytes());
+        let mut games = self
+            .player_games
+            .get(account_id)
+            .unwrap_or_else(|| Vector::new(StorageKey::PlayerGamesPerAccount { account_hash }));
+        games.push(&index);
+        self.player_games.insert(account_id, &games);
+        self.internal_insert_player_game(account_id, index, status);
+    }
+
+    // Moves the game of the player to the index of its new status.
+    pub(crate) fn internal_update_player_game(
+        &mut self,
+        account_id: &AccountId,
+        index: GameIndex,
+        status: GameStatus,
+    ) {
+        for player_games in [
+            &mut self.pending_player_games,
+            &mut self.active_player_games,
+        ] {
+            if let Some(mut games) = player_games.get(account_id) {
+                if games.remove(&index) {
+                    player_games.insert(account_id, &games);
+                }
+            }
+        }
+        self.internal_insert_player_game(account_id, index, status);
+    }
+
+    fn internal_insert_player_game(
+        &mut self,
+        account_id: &AccountId,
+        index: GameIndex,
+        status: GameStatus,
+    ) {
+        let account_hash = env::sha256_array(account_id.as_bytes());
+        let (player_games, key) = match status {
+            GameStatus::Pending => (
+                &mut self.pending_player_games,
+                StorageKey::PendingPlayerGamesPerAccount { account_hash },
+            ),
+            GameStatus::Active => (
+                &mut self.active_player_games,
+                StorageKey::ActivePlayerGamesPerAccount { account_hash },
+            ),
+            GameStatus::Finished => {
+                let mut games = self
+                    .finished_player_games
+                    .get(account_id)
+                    .unwrap_or_else(|| {
+                        Vector::new(StorageKey::FinishedPlayerGamesPerAccount { account_hash })
+                    });
+                games.push(&index);
+                self.finished_player_games.insert(account_id, &games);
+                return;
+            }
+        };
+        let mut games = player_games
+            .get(account_id)
+            .unwrap_or_else(|| UnorderedSet::new(key));
+        games.insert(&index);
+        player_games.insert(account_id, &games);
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod player_games_tests {
+    use near_sdk::{
+        test_utils::{accounts, VMContextBuilder},
+        testing_env, AccountId, ONE_NEAR,
+    };
+
+    use hex_core::Cell;
+
+    use crate::{game::GameStatus, test_utils::create_accepted_game, Contract, MoveType};
+
+    fn get_context(account: AccountId) -> near_sdk::VMContext {
+        VMContextBuilder::new()
+            .predecessor_account_id(account)
+            .attached_deposit(10 * ONE_NEAR)
+            .build()
+    }
+
+    fn indices<T>(games: Vec<(u64, T)>) -> Vec<u64> {
+        games.into_iter().map(|(index, _)| index).collect()
+    }
+
+    #[test]
+    fn test_get_games_for_player() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        create_accepted_game(&mut contract, accounts(0), accounts(1), Some(3), None, None);
+        create_accepted_game(&mut contract, accounts(1), accounts(2), Some(3), None, None);
+        create_accepted_game(&mut contract, accounts(2), accounts(0), Some(3), None, None);
+        create_accepted_game(&mut contract, accounts(0), accounts(0), Some(3), None, None);
+
+        testing_env!(get_context(accounts(2)));
+        contract.make_move(2, MoveType::RESIGN, None).unwrap();
+
+        let games = contract.get_games_for_player(accounts(0), None, None, None);
+        assert_eq!(indices(games), vec![0, 2, 3]);
+        let games = contract.get_games_for_player(accounts(0), Some(1), Some(1), None);
+        assert_eq!(indices(games), vec![2]);
+        let games =
+            contract.get_games_for_player(accounts(0), None, None, Some(GameStatus::Active));
+        assert_eq!(indices(games), vec![0, 3]);
+        let games =
+            contract.get_games_for_player(accounts(2), None, None, Some(GameStatus::Finished));
+        assert_eq!(indices(games), vec![2]);
+        assert!(contract
+            .get_games_for_player(accounts(3), None, None, None)
+            .is_empty());
+
+        testing_env!(get_context(accounts(1)));
+        contract
+            .create_game(accounts(0), accounts(1), Some(3), None, None, None)
+            .unwrap();
+        let games =
+            contract.get_games_for_player(accounts(0), None, None, Some(GameStatus::Pending));
+        assert_eq!(indices(games), vec![4]);
+        let games =
+            contract.get_games_for_player(accounts(0), Some(1), Some(5), Some(GameStatus::Active));
+        assert_eq!(indices(games), vec![3]);
+
+        contract.cancel_game(4).unwrap();
+        let games =
+            contract.get_games_for_player(accounts(0), None, None, Some(GameStatus::Finished));
+        assert_eq!(indices(games), vec![2, 4]);
+        assert!(contract
+            .get_games_for_player(accounts(1), None, None, Some(GameStatus::Pending))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_get_games_awaiting_move() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        create_accepted_game(&mut contract, accounts(0), accounts(1), Some(3), None, None);
+        create_accepted_game(&mut contract, accounts(1), accounts(0), Some(3), None, None);
+        create_accepted_game(&mut contract, accounts(0), accounts(1), Some(3), None, None);
+
+        assert_eq!(
+            indices(contract.get_games_awaiting_move(accounts(0))),
+            vec![0, 2]
+        );
+        assert_eq!(
+            indices(contract.get_games_awaiting_move(accounts(1))),
+            vec![1]
+        );
+
+        testing_env!(get_context(accounts(0)));
+        contract
+            .make_move(0, MoveType::PLACE, Some(Cell::new(1, 1)))
+            .unwrap();
+        contract.make_move(2, MoveType::RESIGN, None).unwrap();
+        assert!(contract.get_games_awaiting_move(accounts(0)).is_empty());
+
+        let mut awaiting = indices(contract.get_games_awaiting_move(accounts(1)));
+        awaiting.sort();
+        assert_eq!(awaiting, vec![0, 1]);
+    }
+}
@@ -0,0 +1,284 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{TreeMap, UnorderedMap};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{near_bindgen, AccountId};
+
+use crate::*;
+
+pub const INITIAL_RATING: u32 = 1500;
+pub const K_FACTOR: f64 = 32.0;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct Rating {
+    pub rating: u32,
+    pub games_played: u32,
+    pub wins: u32,
+    pub losses: u32,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self {
+            rating: INITIAL_RATING,
+            games_played: 0,
+            wins: 0,
+            losses: 0,
+        }
+    }
+}
+
+// Sorts by rating, then by the number of games, both descending, then by the
+// account.
+pub type LeaderboardKey = (u32, u32, AccountId);
+
+fn leaderboard_key(account_id: &AccountId, rating: &Rating) -> LeaderboardKey {
+    (
+        u32::MAX - rating.rating,
+        u32::MAX - rating.games_played,
+        account_id.clone(),
+    )
+}
+
+pub fn elo_delta(winner_rating: u32, loser_rating: u32) -> u32 {
+    let difference = loser_rating as f64 - winner_rating as f64;
+    let expected = 1.0 / (1.0 + 10f64.powf(difference / 400.0));
+    (K_FACTOR * (1.0 - expected)).round() as u32
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_rating(&self, account_id: AccountId, field_size: usize) -> Option<Rating> {
+        self.ratings
+            .get(&field_size)
+            .and_then(|ratings| ratings.get(&account_id))
+    }
+
+    pub fn get_leaderboard(
+        &self,
+        field_size: usize,
+        from: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<(AccountId, Rating)> {
+        let leaderboard = match self.leaderboards.get(&field_size) {
+            Some(leaderboard) => leaderboard,
+            None => return vec![],
+        };
+        leaderboard
+            .iter()
+            .skip(from.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|((_, _, account_id), rating)| (account_id, rating))
+            .collect()
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_update_ratings(&mut self, game: &Game) {
+        let winner = match &game.winner {
+            Some(winner) => winner.clone(),
+            None => return,
+        };
+        // Rectangular boards favour one of the players, such games aren't rated.
+        // Games between two accounts are played only after the opponent has
+        // accepted them, and a game that was never accepted has no winner.
+        if game.first_player == game.second_player || !game.state.board.is_square() {
+            return;
+        }
+        let loser = if winner == game.first_player {
+            game.second_player.clone()
+        } else {
+            game.first_player.clone()
+        };
+
+        let field_size = game.state.board.width;
+        let mut ratings = self.ratings.get(&field_size).unwrap_or_else(|| {
+            UnorderedMap::new(StorageKey::RatingsPerSize {
+                field_size: field_size as u64,
+            })
+        });
+        let mut leaderboard = self.leaderboards.get(&field_size).unwrap_or_else(|| {
+            TreeMap::new(StorageKey::LeaderboardPerSize {
+                field_size: field_size as u64,
+            })
+        });
+        let mut winner_rating = ratings.get(&winner).unwrap_or_default();
+        let mut loser_rating = ratings.get(&loser).unwrap_or_default();
+        leaderboard.remove(&leaderboard_key(&winner, &winner_rating));
+        leaderboard.remove(&leaderboard_key(&loser, &loser_rating));
+
+        let delta = elo_delta(winner_rating.rating, loser_rating.rating);
+        winner_rating.rating += delta;
+        winner_rating.games_played += 1;
+        winner_rating.wins += 1;
+        loser_rating.rating = loser_rating.rating.saturating_sub(delta);
+        loser_rating.games_played += 1;
+        loser_rating.losses += 1;
+
+        for (account_id, rating) in [(&winner, &winner_rating), (&loser, &loser_rating)] {
+            HexGameEvent::RatingUpdated {
+                field_size,
+                account_id,
+                rating,
+            }
+            .emit();
+        }
+        ratings.insert(&winner, &winner_rating);
+        ratings.insert(&loser, &loser_rating);
+        self.ratings.insert(&field_size, &ratings);
+        leaderboard.insert(&leaderboard_key(&winner, &winner_rating), &winner_rating);
+        leaderboard.insert(&leaderboard_key(&loser, &loser_rating), &loser_rating);
+        self.leaderboards.insert(&field_size, &leaderboard);
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod rating_tests {
+    use near_sdk::{
+        test_utils::{accounts, VMContextBuilder},
+        testing_env, AccountId, ONE_NEAR,
+    };
+
+    use super::{elo_delta, Rating, INITIAL_RATING};
+    use crate::{
+        error::HexError, game::Rules, test_utils::create_accepted_game, Contract, MoveType,
+    };
+
+    fn get_context(account: AccountId) -> near_sdk::VMContext {
+        VMContextBuilder::new()
+            .predecessor_account_id(account)
+            .attached_deposit(10 * ONE_NEAR)
+            .build()
+    }
+
+    fn play_and_resign(contract: &mut Contract, winner: AccountId, loser: AccountId, size: usize) {
+        testing_env!(get_context(winner.clone()));
+        let index = create_accepted_game(contract, winner, loser.clone(), Some(size), None, None);
+        testing_env!(get_context(loser));
+        contract.make_move(index, MoveType::RESIGN, None).unwrap();
+    }
+
+    #[test]
+    fn test_elo_delta() {
+        assert_eq!(elo_delta(1500, 1500), 16);
+        assert_eq!(elo_delta(1900, 1500), 3);
+        assert_eq!(elo_delta(1500, 1900), 29);
+    }
+
+    #[test]
+    fn test_update_ratings() {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        assert_eq!(contract.get_rating(accounts(0), 5), None);
+
+        play_and_resign(&mut contract, accounts(0), accounts(1), 5);
+        assert_eq!(
+            contract.get_rating(accounts(0), 5),
+            Some(Rating {
+                rating: INITIAL_RATING + 16,
+                games_played: 1,
+                wins: 1,
+                losses: 0,
+            })
+        );
+        assert_eq!(
+            contract.get_rating(accounts(1), 5),
+            Some(Rating {
+                rating: INITIAL_RATING - 16,
+                games_played: 1,
+                wins: 0,
+                losses: 1,
+            })
+        );
+        assert_eq!(contract.get_rating(accounts(0), 11), None);
+
+        play_and_resign(&mut contract, accounts(1), accounts(0), 11);
+        assert_eq!(contract.get_rating(accounts(0), 5).unwrap().games_played, 1);
+        assert_eq!(
+            contract.get_rating(accounts(0), 11).unwrap().rating,
+            INITIAL_RATING - 16
+        );
+    }
+
+    #[test]
+    fn test_self_play_is_not_rated() {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        play_and_resign(&mut contract, accounts(0), accounts(0), 5);
+        assert_eq!(contract.get_rating(accounts(0), 5), None);
+    }
+
+    #[test]
+    fn test_not_accepted_game_is_not_rated() {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        let rules = Rules {
+            timeout: Some(1),
+            ..Rules::default()
+        };
+        testing_env!(get_context(accounts(1)));
+        let index = contract
+            .create_game(accounts(0), accounts(1), Some(5), None, Some(rules), None)
+            .unwrap();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(1))
+            .block_index(10)
+            .build());
+        assert_eq!(
+            contract.claim_timeout(index),
+            Err(HexError::GameNotAccepted)
+        );
+        assert_eq!(
+            contract.make_move(index, MoveType::RESIGN, None),
+            Err(HexError::GameNotAccepted)
+        );
+        contract.cancel_game(index).unwrap();
+        assert_eq!(contract.get_rating(accounts(0), 5), None);
+        assert_eq!(contract.get_rating(accounts(1), 5), None);
+    }
+
+    #[test]
+    fn test_rectangular_board_is_not_rated() {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        let index = create_accepted_game(
+            &mut contract,
+            accounts(0),
+            accounts(1),
+            Some(5),
+            Some(7),
+            None,
+        );
+        testing_env!(get_context(accounts(1)));
+        contract.make_move(index, MoveType::RESIGN, None).unwrap();
+        assert_eq!(contract.get_rating(accounts(0), 5), None);
+    }
+
+    #[test]
+    fn test_get_leaderboard() {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        play_and_resign(&mut contract, accounts(0), accounts(1), 7);
+        play_and_resign(&mut contract, accounts(2), accounts(1), 7);
+        play_and_resign(&mut contract, accounts(2), accounts(3), 7);
+        play_and_resign(&mut contract, accounts(3), accounts(0), 9);
+
+        let leaderboard: Vec<AccountId> = contract
+            .get_leaderboard(7, None, None)
+            .into_iter()
+            .map(|(account, _)| account)
+            .collect();
+        assert_eq!(
+            leaderboard,
+            vec![accounts(2), accounts(0), accounts(3), accounts(1)]
+        );
+
+        let page = contract.get_leaderboard(7, Some(1), Some(2));
+        assert_eq!(page.len(), 2);
+        assert_eq!(page[0].0, accounts(0));
+        assert_eq!(page[1].0, accounts(3));
+        assert!(contract.get_leaderboard(5, None, None).is_empty());
+    }
+}
@@ -0,0 +1,589 @@
+use hex_core::record::{SGF_RESIGN, SGF_SWAP};
+use hex_core::{parse_record, Play};
+use near_sdk::env;
+
+use crate::error::HexError;
+use crate::game::{FinishReason, Game, Handicap, Rules};
+use crate::history::{decode_move, Move};
+use crate::wager::refund_deposit_with_stake;
+use crate::*;
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(']', "\\]")
+}
+
+fn color(player: u8) -> &'static str {
+    if player == 1 {
+        "B"
+    } else {
+        "W"
+    }
+}
+
+fn result(game: &Game) -> Option<String> {
+    let reason = game.finish_reason.as_ref()?;
+    if *reason == FinishReason::Cancellation {
+        return Some("Void".to_string());
+    }
+    let winner = game.winner.as_ref()?;
+    let winner_color = if *winner == game.first_player {
+        "B"
+    } else {
+        "W"
+    };
+    let suffix = match reason {
+        FinishReason::Connection => "",
+        FinishReason::Resignation => "R",
+        FinishReason::Timeout => "T",
+        FinishReason::Cancellation => unreachable!(),
+    };
+    Some(format!("{}+{}", winner_color, suffix))
+}
+
+// The first player is written as Black, who connects the top and the bottom
+// rows like in HexGui.
+pub fn to_sgf(game: &Game, moves: &[u16]) -> String {
+    let board = &game.state.board;
+    let size = if board.is_square() {
+        board.width.to_string()
+    } else {
+        format!("{}:{}", board.width, board.height)
+    };
+    let mut sgf = format!(
+        "(;FF[4]GM[11]SZ[{}]PB[{}]PW[{}]",
+        size,
+        escape(game.first_player.as_str()),
+        escape(game.second_player.as_str())
+    );
+    if let Some(result) = result(game) {
+        sgf.push_str(&format!("RE[{}]", result));
+    }
+    if let Some(handicap) = game.rules.handicap.as_ref().filter(|x| !x.cells.is_empty()) {
+        sgf.push_str(&format!("A{}", color(handicap.player)));
+        for cell in &handicap.cells {
+            sgf.push_str(&format!("[{}]", cell.to_notation()));
+        }
+    }
+
+    for (ply, &code) in moves.iter().enumerate() {
+        let mv = decode_move(code, ply, board.width);
+        let value = match mv.move_type {
+            MoveType::PLACE => mv.cell.unwrap().to_notation(),
+            MoveType::SWAP => SGF_SWAP.to_string(),
+            MoveType::RESIGN => SGF_RESIGN.to_string(),
+            // The turn lost in a collision is written as a pass, like in
+            // HSGF, so that the colours keep alternating.
+            MoveType::COLLISION => String::new(),
+        };
+        sgf.push_str(&format!(";{}[{}]", color(mv.player), value));
+    }
+    sgf.push(')');
+    sgf
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct Position {
+    pub size: Option<(usize, usize)>,
+    pub handicap: Option<Handicap>,
+    pub moves: Vec<Move>,
+}
+
+pub fn parse_position(position: &str) -> Result<Position, HexError> {
+    let record = parse_record(position).map_err(HexError::InvalidRecord)?;
+    // The setup counters of a record are the handicap of one of the players.
+    let handicap = match record.setup.first() {
+        Some(&(player, _)) => {
+            if record.setup.iter().any(|&(color, _)| color != player) {
+                return Err(HexError::InvalidRecord(
+                    "Setup counters of both players aren't supported.".to_string(),
+                ));
+            }
+            Some(Handicap {
+                player,
+                cells: record.setup.into_iter().map(|(_, cell)| cell).collect(),
+            })
+        }
+        None => None,
+    };
+    let moves = record
+        .moves
+        .into_iter()
+        .map(|(player, play)| match play {
+            Play::Place(cell) => Move {
+                move_type: MoveType::PLACE,
+                cell: Some(cell),
+                player,
+            },
+            Play::Swap => Move {
+                move_type: MoveType::SWAP,
+                cell: None,
+                player,
+            },
+        })
+        .collect();
+    Ok(Position {
+        size: record.size,
+        handicap,
+        moves,
+    })
+}
+
+#[near_bindgen]
+impl Contract {
+    #[handle_result]
+    pub fn export_sgf(&self, index: GameIndex) -> Result<String, HexError> {
+        let game_with_data = self.games.get(index).ok_or(HexError::GameNotFound)?;
+        game_with_data.check_position_visible()?;
+        Ok(to_sgf(&game_with_data.game, &game_with_data.moves.to_vec()))
+    }
+
+    #[payable]
+    #[handle_result]
+    pub fn create_game_from_position(
+        &mut self,
+        first_player: AccountId,
+        second_player: AccountId,
+        position: String,
+        field_size: Option<usize>,
+        field_height: Option<usize>,
+        rules: Option<Rules>,
+    ) -> Result<GameIndex, HexError> {
+        let initial_storage_usage = env::storage_usage();
+
+        // The counters of the position are public.
+        if rules.as_ref().is_some_and(|x| x.dark) {
+            return Err(HexError::DarkGameFromPosition);
+        }
+        let position = parse_position(&position)?;
+        let (width, height) = match (position.size, field_size) {
+            (Some(size), None) => size,
+            (sgf_size, Some(width)) => {
+                let size = (width, field_height.unwrap_or(width));
+                if sgf_size.is_some_and(|x| x != size) {
+                    return Err(HexError::SizeMismatch);
+                }
+                size
+            }
+            (None, None) => (11, 11),
+        };
+
+        let mut game_with_data =
+            GameWithData::new(self.games.len(), first_player, second_player, width, height)?;
+        let mut rules = rules.unwrap_or_default();
+        if let Some(handicap) = position.handicap {
+            if rules.handicap.as_ref().is_some_and(|x| *x != handicap) {
+                return Err(HexError::InvalidRecord(
+                    "The setup counters differ from the handicap of the rules.".to_string(),
+                ));
+            }
+            rules.handicap = Some(handicap);
+        }
+        game_with_data.game.set_rules(rules)?;
+        game_with_data.setup_position(&position.moves)?;
+        game_with_data
+            .game
+            .set_stake(&env::predecessor_account_id(), 0)?;
+        let index = self.internal_add_game(&game_with_data);
+
+        let required_storage_in_bytes = env::storage_usage() - initial_storage_usage;
+        refund_deposit_with_stake(required_storage_in_bytes, 0)?;
+        Ok(index)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod sgf_tests {
+    use near_sdk::{
+        test_utils::{accounts, VMContextBuilder},
+        testing_env, AccountId, ONE_NEAR,
+    };
+
+    use hex_core::Cell;
+
+    use super::{escape, parse_position, to_sgf, Position};
+    use crate::{
+        error::HexError,
+        game::{FinishReason, Game, Handicap, Rules},
+        history::{encode_cell, encode_resign, Move, SWAP_MOVE},
+        test_utils::create_accepted_game,
+        Contract, MoveType,
+    };
+
+    fn get_context(account: AccountId) -> near_sdk::VMContext {
+        VMContextBuilder::new()
+            .predecessor_account_id(account)
+            .attached_deposit(10 * ONE_NEAR)
+            .build()
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("alice"), "alice");
+        assert_eq!(escape("a]b\\c"), "a\\]b\\\\c");
+    }
+
+    #[test]
+    fn test_to_sgf() {
+        let mut game = Game::new(accounts(0), accounts(1), 11, 11).unwrap();
+        assert_eq!(to_sgf(&game, &[]), "(;FF[4]GM[11]SZ[11]PB[alice]PW[bob])");
+
+        let moves = vec![
+            encode_cell(&Cell::new(5, 5), 11),
+            SWAP_MOVE,
+            encode_cell(&Cell::new(0, 10), 11),
+            encode_resign(2),
+        ];
+        game.finish(accounts(0), FinishReason::Resignation);
+        assert_eq!(
+            to_sgf(&game, &moves),
+            "(;FF[4]GM[11]SZ[11]PB[alice]PW[bob]RE[B+R];B[f6];W[swap-pieces];B[a11];W[resign])"
+        );
+    }
+
+    #[test]
+    fn test_to_sgf_results() {
+        let mut game = Game::new(accounts(0), accounts(1), 7, 5).unwrap();
+        game.finish(accounts(1), FinishReason::Connection);
+        assert_eq!(
+            to_sgf(&game, &[]),
+            "(;FF[4]GM[11]SZ[7:5]PB[alice]PW[bob]RE[W+])"
+        );
+
+        let mut game = Game::new(accounts(0), accounts(1), 5, 5).unwrap();
+        game.finish(accounts(0), FinishReason::Timeout);
+        assert!(to_sgf(&game, &[]).contains("RE[B+T]"));
+
+        let mut game = Game::new(accounts(0), accounts(1), 5, 5).unwrap();
+        game.set_stake(&accounts(0), ONE_NEAR).unwrap();
+        game.cancel().unwrap();
+        assert!(to_sgf(&game, &[]).contains("RE[Void]"));
+    }
+
+    #[test]
+    fn test_export_sgf() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        let id = create_accepted_game(&mut contract, accounts(0), accounts(1), Some(2), None, None);
+
+        testing_env!(get_context(accounts(0)));
+        contract
+            .make_move(id, MoveType::PLACE, Some(Cell::new(0, 0)))
+            .unwrap();
+        testing_env!(get_context(accounts(1)));
+        contract
+            .make_move(id, MoveType::PLACE, Some(Cell::new(1, 0)))
+            .unwrap();
+        testing_env!(get_context(accounts(0)));
+        contract
+            .make_move(id, MoveType::PLACE, Some(Cell::new(0, 1)))
+            .unwrap();
+
+        assert_eq!(
+            contract.export_sgf(id).unwrap(),
+            "(;FF[4]GM[11]SZ[2]PB[alice]PW[bob]RE[B+];B[a1];W[b1];B[a2])"
+        );
+    }
+
+    fn place(x: usize, y: usize, player: u8) -> Move {
+        Move {
+            move_type: MoveType::PLACE,
+            cell: Some(Cell::new(x, y)),
+            player,
+        }
+    }
+
+    #[test]
+    fn test_parse_notation_list() {
+        let swap = Move {
+            move_type: MoveType::SWAP,
+            cell: None,
+            player: 2,
+        };
+        assert_eq!(
+            parse_position("f6 swap, a11;b2").unwrap(),
+            Position {
+                size: None,
+                handicap: None,
+                moves: vec![place(5, 5, 1), swap, place(0, 10, 1), place(1, 1, 2)],
+            }
+        );
+        assert_eq!(parse_position("").unwrap().moves, vec![]);
+        assert!(parse_position("a1 resign").is_err());
+        assert!(parse_position("a1 zz").is_err());
+    }
+
+    #[test]
+    fn test_parse_sgf() {
+        let position = parse_position(
+            "(;FF[4]GM[11]SZ[7:5]PB[alice]PW[b\\]ob]C[comment [1\\]];B[a1];W[swap-pieces];B[c3])",
+        )
+        .unwrap();
+        assert_eq!(position.size, Some((7, 5)));
+        assert_eq!(position.moves.len(), 3);
+        assert_eq!(position.moves[0], place(0, 0, 1));
+        assert_eq!(position.moves[1].move_type, MoveType::SWAP);
+        assert_eq!(position.moves[2], place(2, 2, 1));
+
+        let mut game = Game::new(accounts(0), accounts(1), 11, 11).unwrap();
+        game.finish(accounts(0), FinishReason::Resignation);
+        let moves = vec![encode_cell(&Cell::new(5, 5), 11), SWAP_MOVE];
+        let position = parse_position(&to_sgf(&game, &moves)).unwrap();
+        assert_eq!(position.size, Some((11, 11)));
+        assert_eq!(position.moves.len(), 2);
+
+        assert!(parse_position("(;SZ[5];B[a1](;W[b2])(;W[c2]))").is_err());
+        assert!(parse_position("(;SZ[5];B[a1]").is_err());
+        assert!(parse_position("(;SZ[x];B[a1])").is_err());
+        assert!(parse_position("(;SZ[5];B[a1];W[resign])").is_err());
+
+        let position = parse_position("(;SZ[5]AW[b2][c3];B[a1])").unwrap();
+        assert_eq!(
+            position.handicap,
+            Some(Handicap {
+                player: 2,
+                cells: vec![Cell::new(1, 1), Cell::new(2, 2)],
+            })
+        );
+        assert_eq!(position.moves, vec![place(0, 0, 1)]);
+        assert_eq!(
+            parse_position("(;SZ[5]AB[b2]AW[c3];W[a1])"),
+            Err(HexError::InvalidRecord(
+                "Setup counters of both players aren't supported.".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_create_game_from_position() {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        let id = contract
+            .create_game_from_position(
+                accounts(0),
+                accounts(1),
+                "(;FF[4]GM[11]SZ[5];B[b4];W[swap-pieces];B[e5])".to_string(),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        let game = contract.get_game(id).unwrap();
+        assert_eq!((game.state.board.width, game.state.board.height), (5, 5));
+        assert_eq!(game.state.turn, 3);
+        assert_eq!(game.state.board.get_cell(&Cell::new(3, 1)).unwrap(), 2);
+        assert_eq!(game.state.board.get_cell(&Cell::new(4, 4)).unwrap(), 1);
+        assert_eq!(contract.get_moves(id, None, None).unwrap().len(), 3);
+        assert_eq!(game.pending_acceptance, Some(accounts(1)));
+
+        testing_env!(get_context(accounts(1)));
+        assert_eq!(
+            contract.make_move_notation(id, "a1".to_string()),
+            Err(HexError::GameNotAccepted)
+        );
+        contract.accept_game(id).unwrap();
+        assert_eq!(contract.get_games_awaiting_move(accounts(1))[0].0, id);
+        contract.make_move_notation(id, "a1".to_string()).unwrap();
+
+        // Nobody can set up a game for other players.
+        testing_env!(get_context(accounts(2)));
+        assert_eq!(
+            contract.create_game_from_position(
+                accounts(0),
+                accounts(1),
+                "a1".to_string(),
+                None,
+                None,
+                None,
+            ),
+            Err(HexError::NotAPlayer)
+        );
+    }
+
+    #[test]
+    fn test_create_game_from_handicap_record() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        let handicap = Handicap {
+            player: 1,
+            cells: vec![Cell::new(1, 1), Cell::new(3, 3)],
+        };
+        let rules = Rules {
+            handicap: Some(handicap.clone()),
+            ..Rules::default()
+        };
+        let id = create_accepted_game(
+            &mut contract,
+            accounts(0),
+            accounts(1),
+            Some(5),
+            None,
+            Some(rules.clone()),
+        );
+        testing_env!(get_context(accounts(0)));
+        contract.make_move_notation(id, "c3".to_string()).unwrap();
+        testing_env!(get_context(accounts(1)));
+        contract.make_move_notation(id, "a5".to_string()).unwrap();
+        let sgf = contract.export_sgf(id).unwrap();
+        assert_eq!(
+            sgf,
+            "(;FF[4]GM[11]SZ[5]PB[alice]PW[bob]AB[b2][d4];B[c3];W[a5])"
+        );
+
+        let copy = contract
+            .create_game_from_position(accounts(1), accounts(0), sgf.clone(), None, None, None)
+            .unwrap();
+        let game = contract.get_game(copy).unwrap();
+        assert_eq!(game.state, contract.get_game(id).unwrap().state);
+        assert_eq!(game.rules.handicap, Some(handicap));
+        assert_eq!(
+            contract.get_moves(copy, None, None).unwrap(),
+            contract.get_moves(id, None, None).unwrap()
+        );
+        assert_eq!(
+            contract.export_sgf(copy).unwrap(),
+            "(;FF[4]GM[11]SZ[5]PB[bob]PW[alice]AB[b2][d4];B[c3];W[a5])"
+        );
+
+        contract
+            .create_game_from_position(
+                accounts(1),
+                accounts(0),
+                sgf.clone(),
+                None,
+                None,
+                Some(rules),
+            )
+            .unwrap();
+        let other_rules = Rules {
+            handicap: Some(Handicap {
+                player: 1,
+                cells: vec![Cell::new(1, 1)],
+            }),
+            ..Rules::default()
+        };
+        assert_eq!(
+            contract.create_game_from_position(
+                accounts(1),
+                accounts(0),
+                sgf,
+                None,
+                None,
+                Some(other_rules),
+            ),
+            Err(HexError::InvalidRecord(
+                "The setup counters differ from the handicap of the rules.".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_create_dark_game_from_position() {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        let rules = Rules {
+            dark: true,
+            timeout: Some(100),
+            ..Rules::default()
+        };
+        assert_eq!(
+            contract.create_game_from_position(
+                accounts(0),
+                accounts(1),
+                "a1 b2".to_string(),
+                Some(3),
+                None,
+                Some(rules),
+            ),
+            Err(HexError::DarkGameFromPosition)
+        );
+    }
+
+    #[test]
+    fn test_create_game_from_notation_list() {
+        testing_env!(get_context(accounts(1)));
+        let mut contract = Contract::new(None);
+        let id = contract
+            .create_game_from_position(
+                accounts(0),
+                accounts(1),
+                "a1 b1".to_string(),
+                Some(3),
+                Some(2),
+                None,
+            )
+            .unwrap();
+        let game = contract.get_game(id).unwrap();
+        assert_eq!((game.state.board.width, game.state.board.height), (3, 2));
+        assert_eq!(game.state.turn, 2);
+        assert!(!game.is_finished);
+    }
+
+    #[test]
+    fn test_create_game_from_won_position() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        assert_eq!(
+            contract.create_game_from_position(
+                accounts(0),
+                accounts(1),
+                "a1 b1 a2".to_string(),
+                Some(2),
+                None,
+                None,
+            ),
+            Err(HexError::PositionAlreadyWon)
+        );
+    }
+
+    #[test]
+    fn test_create_game_from_position_wrong_order() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        assert_eq!(
+            contract.create_game_from_position(
+                accounts(0),
+                accounts(1),
+                "(;SZ[5];B[a1];B[b2])".to_string(),
+                None,
+                None,
+                None,
+            ),
+            Err(HexError::MovesOutOfOrder)
+        );
+    }
+
+    #[test]
+    fn test_create_game_from_position_size_mismatch() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        assert_eq!(
+            contract.create_game_from_position(
+                accounts(0),
+                accounts(1),
+                "(;SZ[5];B[a1])".to_string(),
+                Some(7),
+                None,
+                None,
+            ),
+            Err(HexError::SizeMismatch)
+        );
+    }
+
+    #[test]
+    fn test_create_game_from_position_occupied_cell() {
+        testing_env!(get_context(accounts(2)));
+        let mut contract = Contract::new(None);
+        assert_eq!(
+            contract.create_game_from_position(
+                accounts(0),
+                accounts(1),
+                "c3 d4 c3".to_string(),
+                None,
+                None,
+                None,
+            ),
+            Err(HexError::CellOccupied)
+        );
+    }
+}
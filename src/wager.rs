@@ -0,0 +1,571 @@
+use near_contract_standards::fungible_token::core::ext_ft_core;
+use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+use near_sdk::json_types::U128;
+use near_sdk::{
+    env, near_bindgen, AccountId, Balance, FunctionError, Gas, PromiseError, PromiseOrValue,
+    StorageUsage,
+};
+
+use crate::*;
+
+pub const GAS_FOR_FT_TRANSFER: Gas = Gas(10_000_000_000_000);
+pub const GAS_FOR_RESOLVE_STAKE_TRANSFER: Gas = Gas(10_000_000_000_000);
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum StakeMessage {
+    CreateGame {
+        first_player: AccountId,
+        second_player: AccountId,
+        field_size: Option<usize>,
+        field_height: Option<usize>,
+        rules: Option<Rules>,
+    },
+    AcceptGame {
+        index: GameIndex,
+    },
+    PostChallenge {
+        field_size: Option<usize>,
+        field_height: Option<usize>,
+        rules: Option<Rules>,
+        opponent: Option<AccountId>,
+    },
+    AcceptChallenge {
+        index: ChallengeIndex,
+    },
+}
+
+pub(crate) fn refund_deposit_with_stake(
+    storage_used: StorageUsage,
+    stake: Balance,
+) -> Result<(), HexError> {
+    let required_cost = env::storage_byte_cost() * Balance::from(storage_used) + stake;
+    let attached_deposit = env::attached_deposit();
+
+    if required_cost > attached_deposit {
+        return Err(HexError::InsufficientDeposit(required_cost));
+    }
+
+    let refund = attached_deposit - required_cost;
+    if refund > 1 {
+        Promise::new(env::predecessor_account_id()).transfer(refund);
+    }
+    Ok(())
+}
+
+// Games and challenges staked with tokens are paid from the storage deposit of
+// the sender, `ft_on_transfer` can't attach NEAR.
+pub(crate) fn charge_storage_deposit(
+    contract: &mut Contract,
+    account_id: &AccountId,
+    initial_storage_usage: StorageUsage,
+) -> Result<(), HexError> {
+    let storage_used = env::storage_usage().saturating_sub(initial_storage_usage);
+    let required_cost = env::storage_byte_cost() * Balance::from(storage_used);
+    let balance = contract.storage_deposits.get(account_id).unwrap_or(0);
+    if required_cost > balance {
+        return Err(HexError::InsufficientStorageDeposit(required_cost));
+    }
+    contract
+        .storage_deposits
+        .insert(account_id, &(balance - required_cost));
+    Ok(())
+}
+
+// Returns the cost of the storage freed since `initial_storage_usage`. Token
+// stakes paid for it from the storage deposit, which gets it back, otherwise
+// it's returned to be transferred with the stake.
+pub(crate) fn release_storage(
+    contract: &mut Contract,
+    account_id: &AccountId,
+    token_staked: bool,
+    initial_storage_usage: StorageUsage,
+) -> Balance {
+    let storage_freed = initial_storage_usage.saturating_sub(env::storage_usage());
+    let cost = env::storage_byte_cost() * Balance::from(storage_freed);
+    if !token_staked {
+        return cost;
+    }
+    let balance = contract.storage_deposits.get(account_id).unwrap_or(0);
+    contract
+        .storage_deposits
+        .insert(account_id, &(balance + cost));
+    0
+}
+
+pub(crate) fn transfer_stake(
+    account_id: AccountId,
+    token_id: Option<AccountId>,
+    amount: Balance,
+) -> Promise {
+    match token_id {
+        None => Promise::new(account_id).transfer(amount),
+        Some(token_id) => ext_ft_core::ext(token_id.clone())
+            .with_attached_deposit(1)
+            .with_static_gas(GAS_FOR_FT_TRANSFER)
+            .ft_transfer(
+                account_id.clone(),
+                U128(amount),
+                Some("Hex game stake".to_string()),
+            )
+            .then(
+                Contract::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_STAKE_TRANSFER)
+                    .resolve_stake_transfer(account_id, token_id, U128(amount)),
+            ),
+    }
+}
+
+pub(crate) fn pay_out(game: &Game) {
+    if game.stake.0 == 0 {
+        return;
+    }
+    if let Some(winner) = &game.winner {
+        transfer_stake(winner.clone(), game.stake_token.clone(), 2 * game.stake.0);
+    }
+}
+
+pub(crate) fn refund_stake(account_id: AccountId, game: &Game) {
+    if game.stake.0 > 0 {
+        transfer_stake(account_id, game.stake_token.clone(), game.stake.0);
+    }
+}
+
+#[near_bindgen]
+impl FungibleTokenReceiver for Contract {
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        self.internal_ft_on_transfer(sender_id, amount, msg)
+            .unwrap_or_else(|e| e.panic())
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> Result<PromiseOrValue<U128>, HexError> {
+        let token_id = env::predecessor_account_id();
+        if !self.accepted_tokens.contains(&token_id) {
+            return Err(HexError::TokenNotAccepted);
+        }
+        let message: StakeMessage =
+            near_sdk::serde_json::from_str(&msg).map_err(|_| HexError::IncorrectTransferMessage)?;
+        let initial_storage_usage = env::storage_usage();
+
+        match message {
+            StakeMessage::CreateGame {
+                first_player,
+                second_player,
+                field_size,
+                field_height,
+                rules,
+            } => {
+                if amount.0 == 0 {
+                    return Err(HexError::ZeroStake);
+                }
+                let width = field_size.unwrap_or(11);
+                let height = field_height.unwrap_or(width);
+                let mut game_with_data = GameWithData::new(
+                    self.games.len(),
+                    first_player,
+                    second_player,
+                    width,
+                    height,
+                )?;
+                game_with_data.game.set_rules(rules.unwrap_or_default())?;
+                game_with_data.game.set_stake(&sender_id, amount.0)?;
+                game_with_data.game.stake_token = Some(token_id);
+                self.internal_add_game(&game_with_data);
+                charge_storage_deposit(self, &sender_id, initial_storage_usage)?;
+                Ok(PromiseOrValue::Value(U128(0)))
+            }
+            StakeMessage::AcceptGame { index } => {
+                let mut game_with_data = self.games.get(index).ok_or(HexError::GameNotFound)?;
+                if game_with_data.game.is_finished {
+                    return Err(HexError::GameFinished);
+                }
+                if game_with_data.game.stake_token.as_ref() != Some(&token_id) {
+                    return Err(HexError::WrongStakeToken);
+                }
+                let stake = game_with_data.game.stake.0;
+                if amount.0 < stake {
+                    return Err(HexError::NotEnoughTokens);
+                }
+
+                game_with_data.game.accept(&sender_id)?;
+                self.internal_on_game_status_changed(index, &game_with_data.game);
+                self.games.replace(index, &game_with_data);
+                HexGameEvent::GameAccepted {
+                    game_id: index,
+                    account_id: &sender_id,
+                }
+                .emit();
+                Ok(PromiseOrValue::Value(U128(amount.0 - stake)))
+            }
+            StakeMessage::PostChallenge {
+                field_size,
+                field_height,
+                rules,
+                opponent,
+            } => {
+                if amount.0 == 0 {
+                    return Err(HexError::ZeroStake);
+                }
+                let field_size = field_size.unwrap_or(11);
+                self.internal_post_challenge(Challenge {
+                    challenger: sender_id.clone(),
+                    opponent,
+                    field_size,
+                    field_height: field_height.unwrap_or(field_size),
+                    rules: rules.unwrap_or_default(),
+                    stake: amount,
+                    stake_token: Some(token_id),
+                })?;
+                charge_storage_deposit(self, &sender_id, initial_storage_usage)?;
+                Ok(PromiseOrValue::Value(U128(0)))
+            }
+            StakeMessage::AcceptChallenge { index } => {
+                let challenge = self
+                    .challenges
+                    .get(&index)
+                    .ok_or(HexError::ChallengeNotFound)?;
+                if challenge.stake_token.as_ref() != Some(&token_id) {
+                    return Err(HexError::WrongStakeToken);
+                }
+                let stake = challenge.stake.0;
+                if amount.0 < stake {
+                    return Err(HexError::NotEnoughTokens);
+                }
+
+                self.internal_accept_challenge(index, sender_id.clone())?;
+                charge_storage_deposit(self, &sender_id, initial_storage_usage)?;
+                Ok(PromiseOrValue::Value(U128(amount.0 - stake)))
+            }
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    #[private]
+    pub fn add_accepted_token(&mut self, token_id: AccountId) {
+        self.accepted_tokens.insert(&token_id);
+    }
+
+    #[private]
+    pub fn remove_accepted_token(&mut self, token_id: AccountId) {
+        self.accepted_tokens.remove(&token_id);
+    }
+
+    // Prepays the storage of games and challenges staked with tokens, the
+    // storage of the deposit record itself is paid from the attached deposit.
+    #[payable]
+    #[handle_result]
+    pub fn storage_deposit(&mut self, account_id: Option<AccountId>) -> Result<U128, HexError> {
+        let initial_storage_usage = env::storage_usage();
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let balance = self.storage_deposits.get(&account_id).unwrap_or(0);
+        self.storage_deposits.insert(&account_id, &balance);
+        let storage_used = env::storage_usage() - initial_storage_usage;
+        let required_cost = env::storage_byte_cost() * Balance::from(storage_used);
+        let attached_deposit = env::attached_deposit();
+        if required_cost > attached_deposit {
+            return Err(HexError::InsufficientDeposit(required_cost));
+        }
+        let balance = balance + attached_deposit - required_cost;
+        self.storage_deposits.insert(&account_id, &balance);
+        Ok(U128(balance))
+    }
+
+    pub fn storage_balance_of(&self, account_id: AccountId) -> U128 {
+        U128(self.storage_deposits.get(&account_id).unwrap_or(0))
+    }
+
+    #[handle_result]
+    pub fn storage_withdraw(&mut self) -> Result<Promise, HexError> {
+        let account_id = env::predecessor_account_id();
+        let amount = self.storage_deposits.get(&account_id).unwrap_or(0);
+        if amount == 0 {
+            return Err(HexError::NothingToWithdraw);
+        }
+        self.storage_deposits.insert(&account_id, &0);
+        Ok(Promise::new(account_id).transfer(amount))
+    }
+
+    pub fn get_accepted_tokens(&self) -> Vec<AccountId> {
+        self.accepted_tokens.to_vec()
+    }
+
+    pub fn get_unclaimed_stake(&self, account_id: AccountId, token_id: AccountId) -> U128 {
+        U128(
+            self.unclaimed_stakes
+                .get(&(account_id, token_id))
+                .unwrap_or(0),
+        )
+    }
+
+    #[handle_result]
+    pub fn withdraw_unclaimed_stake(&mut self, token_id: AccountId) -> Result<Promise, HexError> {
+        let account_id = env::predecessor_account_id();
+        let amount = self
+            .unclaimed_stakes
+            .remove(&(account_id.clone(), token_id.clone()))
+            .ok_or(HexError::NothingToWithdraw)?;
+        Ok(transfer_stake(account_id, Some(token_id), amount))
+    }
+
+    #[private]
+    pub fn resolve_stake_transfer(
+        &mut self,
+        account_id: AccountId,
+        token_id: AccountId,
+        amount: U128,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) -> bool {
+        if result.is_ok() {
+            return true;
+        }
+        HexGameEvent::StakeUnclaimed {
+            account_id: &account_id,
+            token_id: &token_id,
+            amount,
+        }
+        .emit();
+        let key = (account_id, token_id);
+        let unclaimed = self.unclaimed_stakes.get(&key).unwrap_or(0);
+        self.unclaimed_stakes.insert(&key, &(unclaimed + amount.0));
+        false
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod wager_tests {
+    use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+    use near_sdk::{
+        json_types::U128,
+        mock::VmAction,
+        test_utils::{accounts, get_created_receipts, VMContextBuilder},
+        testing_env, AccountId, PromiseError, PromiseOrValue, ONE_NEAR,
+    };
+
+    use hex_core::Cell;
+
+    use crate::{error::HexError, Contract, MoveType};
+
+    fn get_context(account: AccountId) -> near_sdk::VMContext {
+        VMContextBuilder::new()
+            .predecessor_account_id(account)
+            .build()
+    }
+
+    fn token() -> AccountId {
+        "wrap.testnet".parse().unwrap()
+    }
+
+    fn unwrap_value(value: PromiseOrValue<U128>) -> U128 {
+        match value {
+            PromiseOrValue::Value(value) => value,
+            PromiseOrValue::Promise(_) => panic!("Expected value"),
+        }
+    }
+
+    fn get_contract() -> Contract {
+        testing_env!(get_context(accounts(0)));
+        let mut contract = Contract::new(None);
+        contract.add_accepted_token(token());
+        for account in [accounts(0), accounts(1)] {
+            testing_env!(VMContextBuilder::new()
+                .predecessor_account_id(account)
+                .attached_deposit(ONE_NEAR)
+                .build());
+            contract.storage_deposit(None).unwrap();
+        }
+        contract
+    }
+
+    #[test]
+    fn test_ft_create_and_accept_game() {
+        let mut contract = get_contract();
+        assert_eq!(contract.get_accepted_tokens(), vec![token()]);
+
+        testing_env!(get_context(token()));
+        let msg =
+            r#"{"CreateGame": {"first_player": "alice", "second_player": "bob", "field_size": 5}}"#;
+        let unused = contract.ft_on_transfer(accounts(1), U128(100), msg.to_string());
+        assert_eq!(unwrap_value(unused), U128(0));
+
+        let game = contract.get_game(0).unwrap();
+        assert_eq!(game.stake, U128(100));
+        assert_eq!(game.stake_token, Some(token()));
+        assert_eq!(game.pending_acceptance, Some(accounts(0)));
+
+        let msg = r#"{"AcceptGame": {"index": 0}}"#;
+        let unused = contract.ft_on_transfer(accounts(0), U128(150), msg.to_string());
+        assert_eq!(unwrap_value(unused), U128(50));
+        assert_eq!(contract.get_game(0).unwrap().pending_acceptance, None);
+    }
+
+    #[test]
+    fn test_ft_post_and_accept_challenge() {
+        let mut contract = get_contract();
+
+        testing_env!(get_context(token()));
+        let msg = r#"{"PostChallenge": {"field_size": 7, "opponent": "bob"}}"#;
+        let unused = contract.ft_on_transfer(accounts(0), U128(100), msg.to_string());
+        assert_eq!(unwrap_value(unused), U128(0));
+        let challenge = contract.get_challenge(0).unwrap();
+        assert_eq!(challenge.stake, U128(100));
+        assert_eq!(challenge.stake_token, Some(token()));
+
+        let msg = r#"{"AcceptChallenge": {"index": 0}}"#;
+        let unused = contract.ft_on_transfer(accounts(1), U128(100), msg.to_string());
+        assert_eq!(unwrap_value(unused), U128(0));
+
+        let game = contract.get_game(0).unwrap();
+        assert_eq!(game.first_player, accounts(0));
+        assert_eq!(game.second_player, accounts(1));
+        assert_eq!(game.stake_token, Some(token()));
+    }
+
+    #[test]
+    fn test_ft_cancel_challenge() {
+        let mut contract = get_contract();
+        let deposit = contract.storage_balance_of(accounts(0)).0;
+
+        testing_env!(get_context(token()));
+        let msg = r#"{"PostChallenge": {"field_size": 7}}"#;
+        contract.ft_on_transfer(accounts(0), U128(100), msg.to_string());
+        assert!(contract.storage_balance_of(accounts(0)).0 < deposit);
+
+        testing_env!(get_context(accounts(0)));
+        contract.cancel_challenge(0).unwrap();
+        assert_eq!(contract.storage_balance_of(accounts(0)).0, deposit);
+        let receipts = get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, token());
+    }
+
+    #[test]
+    fn test_ft_not_accepted_token() {
+        let mut contract = get_contract();
+        testing_env!(get_context(accounts(3)));
+        let msg = r#"{"CreateGame": {"first_player": "alice", "second_player": "bob"}}"#;
+        let result = contract.internal_ft_on_transfer(accounts(1), U128(100), msg.to_string());
+        assert_eq!(result.err(), Some(HexError::TokenNotAccepted));
+    }
+
+    #[test]
+    fn test_ft_storage_deposit() {
+        let mut contract = get_contract();
+        let deposit = contract.storage_balance_of(accounts(1)).0;
+        assert!(deposit > 0 && deposit < ONE_NEAR);
+
+        testing_env!(get_context(token()));
+        let msg = r#"{"CreateGame": {"first_player": "alice", "second_player": "bob"}}"#;
+        contract.ft_on_transfer(accounts(1), U128(100), msg.to_string());
+        assert!(contract.storage_balance_of(accounts(1)).0 < deposit);
+
+        // Nobody has paid for the storage of these.
+        let msg = r#"{"CreateGame": {"first_player": "charlie", "second_player": "bob"}}"#;
+        let result = contract.internal_ft_on_transfer(accounts(2), U128(100), msg.to_string());
+        assert!(matches!(
+            result.err(),
+            Some(HexError::InsufficientStorageDeposit(_))
+        ));
+        let msg = r#"{"PostChallenge": {"field_size": 7}}"#;
+        let result = contract.internal_ft_on_transfer(accounts(2), U128(100), msg.to_string());
+        assert!(matches!(
+            result.err(),
+            Some(HexError::InsufficientStorageDeposit(_))
+        ));
+        let result = contract.internal_ft_on_transfer(accounts(0), U128(0), msg.to_string());
+        assert_eq!(result.err(), Some(HexError::ZeroStake));
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build());
+        assert!(matches!(
+            contract.storage_deposit(None),
+            Err(HexError::InsufficientDeposit(_))
+        ));
+
+        testing_env!(get_context(accounts(1)));
+        contract.storage_withdraw().unwrap();
+        assert_eq!(contract.storage_balance_of(accounts(1)), U128(0));
+        assert!(matches!(
+            contract.storage_withdraw(),
+            Err(HexError::NothingToWithdraw)
+        ));
+    }
+
+    #[test]
+    fn test_ft_accept_not_enough() {
+        let mut contract = get_contract();
+        testing_env!(get_context(token()));
+        let msg = r#"{"CreateGame": {"first_player": "alice", "second_player": "bob"}}"#;
+        contract.ft_on_transfer(accounts(1), U128(100), msg.to_string());
+        let msg = r#"{"AcceptGame": {"index": 0}}"#;
+        let result = contract.internal_ft_on_transfer(accounts(0), U128(99), msg.to_string());
+        assert_eq!(result.err(), Some(HexError::NotEnoughTokens));
+    }
+
+    #[test]
+    fn test_ft_pay_out() {
+        let mut contract = get_contract();
+        testing_env!(get_context(token()));
+        let msg = r#"{"CreateGame": {"first_player": "alice", "second_player": "bob"}}"#;
+        contract.ft_on_transfer(accounts(0), U128(100), msg.to_string());
+        let msg = r#"{"AcceptGame": {"index": 0}}"#;
+        contract.ft_on_transfer(accounts(1), U128(100), msg.to_string());
+
+        testing_env!(get_context(accounts(0)));
+        contract
+            .make_move(0, MoveType::PLACE, Some(Cell::new(5, 5)))
+            .unwrap();
+        contract.make_move(0, MoveType::RESIGN, None).unwrap();
+
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 2);
+        assert_eq!(receipts[0].receiver_id, token());
+        match &receipts[0].actions[0] {
+            VmAction::FunctionCall {
+                function_name,
+                deposit,
+                ..
+            } => {
+                assert_eq!(function_name, "ft_transfer");
+                assert_eq!(*deposit, 1);
+            }
+            _ => panic!("Expected function call"),
+        }
+    }
+
+    #[test]
+    fn test_resolve_stake_transfer_failed() {
+        let mut contract = get_contract();
+        assert!(contract.resolve_stake_transfer(accounts(1), token(), U128(200), Ok(())));
+        assert_eq!(contract.get_unclaimed_stake(accounts(1), token()), U128(0));
+
+        assert!(!contract.resolve_stake_transfer(
+            accounts(1),
+            token(),
+            U128(200),
+            Err(PromiseError::Failed)
+        ));
+        assert_eq!(
+            contract.get_unclaimed_stake(accounts(1), token()),
+            U128(200)
+        );
+
+        testing_env!(get_context(accounts(1)));
+        contract.withdraw_unclaimed_stake(token()).unwrap();
+        assert_eq!(contract.get_unclaimed_stake(accounts(1), token()), U128(0));
+    }
+}